@@ -1,4 +1,37 @@
-enum Decimal {
+use std::fmt;
+
+/// The value of a floating point literal, in the type Groovy gives it
+#[derive(Debug, Clone)]
+// the variants are named after the Java types
+#[allow(clippy::enum_variant_names)]
+pub enum Decimal {
     Float(f32),
     Double(f64),
+    /// An arbitrary precision decimal, held as its text without underscores
+    /// or suffix, e.g. `1.5e10`. This is the type of unsuffixed literals
+    BigDecimal(String),
+}
+
+/// Literals are never NaN, so comparing the bits gives a proper equivalence
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Decimal::Float(a), Decimal::Float(b)) => a.to_bits() == b.to_bits(),
+            (Decimal::Double(a), Decimal::Double(b)) => a.to_bits() == b.to_bits(),
+            (Decimal::BigDecimal(a), Decimal::BigDecimal(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Decimal {}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Decimal::Float(n) => write!(f, "{}f", n),
+            Decimal::Double(n) => write!(f, "{}d", n),
+            Decimal::BigDecimal(n) => f.write_str(n),
+        }
+    }
 }
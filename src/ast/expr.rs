@@ -1,17 +1,38 @@
 use std::rc::Rc;
 
-use crate::ast::{
-    AssignmentOperator, BinaryOperator, Field, Identifier, Method, MethodModifier, Parameter, Stmt,
-    Type, Variable, VariableScope,
+use crate::{
+    ast::{
+        AssignmentOperator, BinaryOperator, Decimal, Field, Identifier, Integer, Method,
+        MethodModifier, Parameter, Stmt, Type, UnaryOperator, Variable, VariableScope,
+    },
+    span::{Span, Spanned},
 };
 
-#[derive(Debug)]
-pub enum Expr {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprKind {
     Array {
         values: Vec<Expr>,
         length: Box<Expr>,
         element_type: Type,
     },
+    /// Assigns to a variable or property, `x = 1` or `x += 1`
+    Assignment {
+        target: Box<Expr>,
+        op: AssignmentOperator,
+        value: Box<Expr>,
+    },
     Attribute,
     BinaryOp {
         left: Box<Expr>,
@@ -56,6 +77,8 @@ pub enum Expr {
     },
     ElvisOperator,
     Empty,
+    /// An expression which could not be parsed
+    Error,
     /// Represents field access, `this.foo`
     Field {
         field: Box<Field>,
@@ -81,20 +104,33 @@ pub enum Expr {
     Map {
         elements: Vec<MapEntry>,
     },
+    /// A call of a method by name, `foo.bar(1)` or `bar(1)`
     MethodCall {
-        object: Box<Expr>,
-        method: Box<Expr>,
-        arguments: Box<Expr>,
-        implicit_this: bool,
-        spread_safe: bool,
-        safe: bool,
-        target: Rc<Method>,
+        /// The object the method is called on, which is missing when calling
+        /// a method of `this` implicitly
+        object: Option<Box<Expr>>,
+        method: Spanned<Identifier>,
+        arguments: Vec<Expr>,
+        /// The method being called, once it has been resolved
+        target: Option<Rc<Method>>,
     },
     MethodPointer,
     Not,
-    Postfix,
-    Prefix,
-    Property,
+    /// `i++` or `i--`
+    Postfix {
+        expr: Box<Expr>,
+        op: UnaryOperator,
+    },
+    /// `++i` or `--i`
+    Prefix {
+        op: UnaryOperator,
+        expr: Box<Expr>,
+    },
+    /// Access to a property of an object, `foo.bar`
+    Property {
+        object: Box<Expr>,
+        property: Spanned<Identifier>,
+    },
     Range,
     Spread,
     SpreadMap,
@@ -103,40 +139,36 @@ pub enum Expr {
     Tuple,
     UnaryMinus,
     UnaryPlus,
+    /// A reference to a variable, parameter or property of `this` by name
     Variable {
         name: Identifier,
-        modifiers: Vec<MethodModifier>,
-        in_static_context: bool,
-        is_dynamically_typed: bool,
-        accessed_variable: Box<Variable>,
-        closure_share: bool,
-        use_ref: bool,
-        origin_type: Option<Type>,
     },
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConstExpr {
     True,
     False,
     Null,
     String(String),
-    Number(String),
+    Integer(Integer),
+    Decimal(Decimal),
 }
 
 /// A single key-value map entry
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MapEntry {
     key: Box<Expr>,
     value: Box<Expr>,
 }
 
-#[derive(Debug)]
-pub struct GString(Vec<InterpolatedStringPart>);
+/// A double-quoted string containing interpolations, e.g. `"$a.b and ${c}"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GString(pub Vec<InterpolatedStringPart>);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InterpolatedStringPart {
     Literal(String),
-    Identifier(Identifier),
+    /// Either a `$path.to.value` or a `${ expression }`
     Expression(Box<Expr>),
 }
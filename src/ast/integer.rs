@@ -1,7 +1,28 @@
-enum Integer {
+use std::fmt;
+
+/// The value of an integer literal, in the type Groovy gives it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+// the variants are named after the Java types
+#[allow(clippy::enum_variant_names)]
+pub enum Integer {
     Byte(i8),
     Short(i16),
     Int(i32),
     Long(i64),
     Char(u16),
+    /// An arbitrary precision integer, held as its decimal digits
+    BigInteger(String),
+}
+
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Integer::Byte(n) => write!(f, "{}", n),
+            Integer::Short(n) => write!(f, "{}", n),
+            Integer::Int(n) => write!(f, "{}", n),
+            Integer::Long(n) => write!(f, "{}L", n),
+            Integer::Char(n) => write!(f, "{}", n),
+            Integer::BigInteger(n) => write!(f, "{}G", n),
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, rc::Rc};
+use std::{collections::HashMap, fmt};
 
 mod decimal;
 mod expr;
@@ -9,117 +9,276 @@ mod stmt;
 mod token;
 mod visibility;
 
-use crate::interner::{keywords::Keywords, Symbol};
+use crate::{
+    interner::{keywords::Keywords, Symbol},
+    span::{Span, Spanned},
+};
 
-pub use expr::{ConstExpr, Expr};
+pub use decimal::Decimal;
+pub use expr::{ConstExpr, Expr, ExprKind, GString, InterpolatedStringPart};
+pub use integer::Integer;
 pub use modifier::{ClassModifier, MethodModifier};
 pub use operator::{AssignmentOperator, BinaryOperator, UnaryOperator};
-pub use stmt::Stmt;
-pub use token::{Literal, Token};
+pub use stmt::{Stmt, StmtKind};
+pub use token::{Literal, SpannedToken, Token};
 pub use visibility::Visibility;
 
-#[derive(Debug)]
+/// A whole source file. Groovy files are often scripts, which mix statements
+/// and method definitions with any number of type declarations
+#[derive(Debug, PartialEq, Eq)]
+pub struct CompilationUnit {
+    pub package: Option<Package>,
+    pub imports: Vec<Import>,
+    pub types: Vec<Class>,
+    /// The statements outside of any declaration, which form the body of the
+    /// script
+    pub statements: Vec<Stmt>,
+    /// Methods declared outside of any class, which belong to the script
+    pub methods: Vec<Method>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Package {
+    pub name: QualifiedName,
+    pub annotations: Vec<Annotation>,
+    pub span: Span,
+}
+
+/// A dotted name such as `java.util.List`, keeping the span of each segment
+/// so that each can be navigated to separately
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QualifiedName {
+    pub segments: Vec<Spanned<Identifier>>,
+    pub span: Span,
+}
+
+impl QualifiedName {
+    /// The final segment, which is the simple name of a class
+    pub fn last(&self) -> Spanned<Identifier> {
+        *self.segments.last().unwrap()
+    }
+}
+
+impl fmt::Display for QualifiedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", segment.node.name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Any type declaration. Interfaces, enums and traits are represented as
+/// classes, as they are by the Groovy compiler, and are told apart by `kind`
+#[derive(Debug, PartialEq, Eq)]
 pub struct Class {
-    pub name: Identifier,
+    pub name: Spanned<Identifier>,
+    pub kind: ClassKind,
     pub visibility: Visibility,
     pub modifiers: Vec<ClassModifier>,
-    pub super_class: Option<Rc<Class>>,
+    pub annotations: Vec<Annotation>,
+    pub type_parameters: Vec<TypeParameter>,
+    /// The class named by `extends`. Interfaces, which may extend several
+    /// others, list them in `interfaces` instead
+    pub super_class: Option<Spanned<Type>>,
+    /// Methods and constructors
     pub methods: Vec<Method>,
+    /// Fields declared with a visibility. Properties are kept separately
     pub fields: Vec<Field>,
     pub properties: Vec<Property>,
-    pub interfaces: Vec<Class>,
-    pub mixin: Vec<Mixin>,
+    pub interfaces: Vec<Spanned<Type>>,
+    /// The subclasses allowed to extend a sealed type
+    pub permits: Vec<Spanned<Type>>,
+    /// The components declared in the header of a record, `record P(int x)`
+    pub record_components: Vec<Parameter>,
+    /// The constants of an enum, in order
+    pub enum_constants: Vec<EnumConstant>,
+    /// The `static { }` and `{ }` blocks, in order
+    pub initializers: Vec<Initializer>,
+    /// The Groovydoc comment preceding the declaration
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassKind {
+    Class,
+    Interface,
+    Enum,
+    Trait,
+    Record,
+    /// An annotation type, declared with `@interface`
+    Annotation,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
+pub struct EnumConstant {
+    pub name: Spanned<Identifier>,
+    /// The arguments passed to the enum's constructor
+    pub arguments: Vec<Expr>,
+    /// The class body following the constant, which the compiler turns into
+    /// an anonymous subclass of the enum
+    pub body: Option<Box<Class>>,
+    pub annotations: Vec<Annotation>,
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Method {
-    pub name: Identifier,
+    pub name: Spanned<Identifier>,
     pub visibility: Visibility,
     pub modifiers: Vec<MethodModifier>,
+    pub annotations: Vec<Annotation>,
     pub return_type: Type,
     pub parameters: Vec<Parameter>,
+    /// Whether any parameter has a default value, in which case the compiler
+    /// generates an overload for each number of arguments
     pub has_default_value: bool,
-    pub exceptions: Vec<Class>,
+    /// The types listed after `throws`
+    pub exceptions: Vec<Spanned<Type>>,
     pub static_constructor: bool,
-    pub body: Stmt,
+    /// The body of the method, which is missing for abstract methods
+    pub body: Option<Stmt>,
     pub is_constructor: bool,
+    /// The value given with `default` by a member of an annotation type
+    pub annotation_default: Option<AnnotationValue>,
+    pub doc: Option<String>,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Field {
-    name: Identifier,
-    visibility: Visibility,
-    modifiers: Vec<MethodModifier>,
-    field_type: Type,
-    owner: Class,
-    dynamically_typed: bool,
-    holder: bool,
-    origin_type: Option<Type>,
-    initial_value: Expr,
-}
-
-#[derive(Debug)]
+    pub name: Spanned<Identifier>,
+    pub visibility: Visibility,
+    pub modifiers: Vec<MethodModifier>,
+    pub annotations: Vec<Annotation>,
+    pub field_type: Type,
+    pub initial_value: Option<Expr>,
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+/// A field declared without a visibility. The compiler makes the field
+/// private and generates a public getter, and a setter unless it is final
+#[derive(Debug, PartialEq, Eq)]
 pub struct Property {
-    field: Field,
-    setter_block: Stmt,
-    getter_block: Stmt,
-    modifiers: Vec<MethodModifier>,
+    pub field: Field,
 }
 
-#[derive(Debug)]
-pub struct Interface {}
+/// A block in a class body which runs when the class is loaded, if static,
+/// or else whenever an instance is constructed
+#[derive(Debug, PartialEq, Eq)]
+pub struct Initializer {
+    pub is_static: bool,
+    pub body: Stmt,
+    pub span: Span,
+}
 
-#[derive(Debug)]
-pub struct Mixin {}
+/// A type variable declared by a generic class, `T extends Number`
+#[derive(Debug, PartialEq, Eq)]
+pub struct TypeParameter {
+    pub name: Spanned<Identifier>,
+    /// The types following `extends`, separated by `&`
+    pub bounds: Vec<Spanned<Type>>,
+    pub span: Span,
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Parameter {
     pub param_type: Type,
-    pub name: Identifier,
+    pub name: Spanned<Identifier>,
     pub origin_type: Option<Type>,
     pub dynamically_typed: bool,
     pub closure_shared: bool,
     pub default_value: Option<Expr>,
     pub in_static_context: bool,
     pub modifiers: Vec<MethodModifier>,
+    pub annotations: Vec<Annotation>,
+    /// Declared as `Type... name`, taking any number of arguments as an
+    /// array. Only the last parameter can be varargs
+    pub is_varargs: bool,
+    pub span: Span,
+}
+
+/// An annotation on a declaration, `@Deprecated` or
+/// `@Grab(group = 'org.slf4j', module = 'slf4j-api')`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub name: QualifiedName,
+    pub elements: Vec<AnnotationElement>,
+    pub span: Span,
+}
+
+/// A single argument of an annotation. The name may be left out for the
+/// element called `value`, as in `@SuppressWarnings('unchecked')`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotationElement {
+    pub name: Option<Spanned<Identifier>>,
+    pub value: AnnotationValue,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotationValue {
+    Expr(Expr),
+    /// Another annotation, as in `@Repeated(@Tag('a'))`
+    Annotation(Box<Annotation>),
+    /// A list of values, `['a', 'b']`
+    Array(Vec<AnnotationValue>),
+    /// A closure, which the compiler turns into a class, as in
+    /// `@Memoized(maxCacheSize = { 10 })`
+    Closure(Box<Stmt>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct Import {
-    import_type: Type,
-    alias: Identifier,
-    field_name: Identifier,
-    package_name: Identifier,
-    is_star: bool,
-    is_static: bool,
+    /// The imported class or member. For star imports, this is the package
+    /// or class whose members are all imported
+    pub name: QualifiedName,
+    /// The name given with `as`, if any
+    pub alias: Option<Spanned<Identifier>>,
+    /// `import a.b.*`
+    pub is_star: bool,
+    /// `import static a.B.m`
+    pub is_static: bool,
+    pub annotations: Vec<Annotation>,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum AstNode {
-    Annotated,
     ClassCodeVisitorSupport,
     Class(Class),
     Constructor(Method),
+    /// A region of the file which could not be parsed
+    Error(Span),
     Field(Field),
     Import(Import),
     Method(Method),
-    Module,
+    Module(CompilationUnit),
     Parameter(Parameter),
     Property(Property),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Variable {
-    pub name: Identifier,
+    pub name: Spanned<Identifier>,
     pub type_name: Type,
     pub value: Expr,
     pub in_static_context: bool,
     pub is_dynamically_typed: bool,
     pub is_closure_shared_variable: bool,
     pub modifiers: Vec<MethodModifier>,
+    pub annotations: Vec<Annotation>,
+    pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VariableScope {
     parent: Option<Box<VariableScope>>,
     class_scope: Box<VariableScope>,
@@ -129,7 +288,7 @@ pub struct VariableScope {
     referenced_class_variables: HashMap<Identifier, Variable>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Identifier {
     pub name: Symbol,
 }
@@ -142,7 +301,7 @@ impl Identifier {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Type {
     /// void
     Void,
@@ -152,14 +311,40 @@ pub enum Type {
     Byte,
     /// 2 byte integer
     Short,
-    ///
+    /// 4 byte integer
     Int,
     Double,
     Float,
     Char,
     Long,
-    Class(Identifier),
+    /// A class or type variable, which is resolved by name
+    Class {
+        /// The package or outer classes, `java.util` in `java.util.List`
+        qualifier: Vec<Identifier>,
+        name: Identifier,
+        /// The type arguments, which are `None` for a raw type and empty for
+        /// the diamond `<>`
+        arguments: Option<Vec<Type>>,
+    },
+    /// `?`, `? extends T` or `? super T`, which can only be a type argument
+    Wildcard(Option<WildcardBound>),
     Array(Box<Type>),
     /// Either inferred or dynamic
     Placeholder,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WildcardBound {
+    Extends(Box<Type>),
+    Super(Box<Type>),
+}
+
+impl Type {
+    /// The name of the class this type refers to, ignoring any type arguments
+    pub fn class_name(&self) -> Option<Identifier> {
+        match *self {
+            Type::Class { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+}
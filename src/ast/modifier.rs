@@ -1,11 +1,17 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ClassModifier {
     /// Declaration cannot be overridden
     Final,
     Static,
+    /// Cannot be instantiated
+    Abstract,
+    /// Can only be extended by the classes it permits
+    Sealed,
+    /// Extends a sealed type, but can itself be extended by anything
+    NonSealed,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MethodModifier {
     /// Declaration cannot be overridden
     Final,
@@ -19,4 +25,6 @@ pub enum MethodModifier {
     Volatile,
     /// A native code entrypoint
     Native,
+    /// An interface method with a body
+    Default,
 }
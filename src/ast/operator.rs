@@ -1,5 +1,8 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AssignmentOperator {
+    /// =
+    Assign,
+
     /// +=
     AddAssign,
 
@@ -33,9 +36,6 @@ pub enum AssignmentOperator {
     /// >>=
     ShrAssign,
 
-    /// <<<=
-    UnsignedShlAssign,
-
     /// >>>=
     UnsignedShrAssign,
 
@@ -46,7 +46,7 @@ pub enum AssignmentOperator {
     ElvisAssignment,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BinaryOperator {
     /// +
     Add,
@@ -111,9 +111,6 @@ pub enum BinaryOperator {
     /// >>
     Shr,
 
-    /// <<<
-    UnsignedShl,
-
     /// >>>
     UnsignedShr,
 
@@ -147,6 +144,12 @@ pub enum BinaryOperator {
     /// ..
     InclusiveRange,
 
+    /// <..
+    ExclusiveLeftRange,
+
+    /// <..<
+    ExclusiveBothRange,
+
     /// ?.
     SafeNavigation,
 
@@ -194,7 +197,8 @@ impl BinaryOperator {
             | BinaryOperator::Shr
             | BinaryOperator::ExclusiveRange
             | BinaryOperator::InclusiveRange
-            | BinaryOperator::UnsignedShl
+            | BinaryOperator::ExclusiveLeftRange
+            | BinaryOperator::ExclusiveBothRange
             | BinaryOperator::UnsignedShr => (15, 16),
             BinaryOperator::Add | BinaryOperator::Sub => (17, 18),
             BinaryOperator::Mul | BinaryOperator::Div | BinaryOperator::Rem => (19, 20),
@@ -209,7 +213,7 @@ impl BinaryOperator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UnaryOperator {
     /// !
     LogicalNot,
@@ -225,4 +229,10 @@ pub enum UnaryOperator {
 
     /// -
     Minus,
+
+    /// ++
+    Increment,
+
+    /// --
+    Decrement,
 }
@@ -1,7 +1,22 @@
-use crate::ast::{Expr, Identifier, Parameter, Variable, VariableScope};
+use crate::{
+    ast::{Expr, Identifier, Parameter, Variable, VariableScope},
+    span::{Span, Spanned},
+};
 
-#[derive(Debug)]
-pub enum Stmt {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+impl Stmt {
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StmtKind {
     /// Represents a Groovy `assert` statement
     ///
     /// E.g. `assert  1 == 0, "1 does not equal 0"`
@@ -14,33 +29,65 @@ pub enum Stmt {
         // todo: this shouldn't be optional
         scope: Option<VariableScope>,
     },
+    /// `break`, optionally naming the labeled loop to leave
     Break {
-        label: Option<Identifier>,
+        label: Option<Spanned<Identifier>>,
     },
     Case,
     Catch,
-    Continue,
-    DoWhile,
+    /// A classic `for (init; condition; update)` loop, any part of which may
+    /// be left out. The init may declare several variables, as in
+    /// `for (int i = 0, j = n; i < j; i++, j--)`
+    ClassicFor {
+        init: Vec<Stmt>,
+        condition: Option<Expr>,
+        update: Vec<Expr>,
+        loop_block: Box<Stmt>,
+    },
+    /// `continue`, optionally naming the labeled loop to continue
+    Continue {
+        label: Option<Spanned<Identifier>>,
+    },
+    /// `do { ... } while (condition)`, which runs its block at least once
+    DoWhile {
+        loop_block: Box<Stmt>,
+        condition: Expr,
+    },
     Empty,
+    /// A statement which could not be parsed
+    Error,
     Expression(Expr),
+    /// A loop over the elements of a collection, `for (x in xs)` or
+    /// `for (T x : xs)`
     For {
-        variable: Parameter,
+        variable: Box<Parameter>,
         collection: Expr,
         loop_block: Box<Stmt>,
-        scope: VariableScope,
+        scope: Option<VariableScope>,
     },
     If {
         expr: Expr,
         if_block: Box<Stmt>,
         else_block: Option<Box<Stmt>>,
     },
+    /// A statement preceded by a label, `outer: for (x in xs) { ... }`,
+    /// which `break` and `continue` can refer to
+    Labeled {
+        label: Spanned<Identifier>,
+        stmt: Box<Stmt>,
+    },
+    /// `return`, with no value when the statement ends straight after the
+    /// keyword
     Return {
-        expr: Expr,
+        expr: Option<Expr>,
     },
     Switch,
     Synchronized,
     Throw,
     TryCatch,
-    While,
+    While {
+        condition: Expr,
+        loop_block: Box<Stmt>,
+    },
     VariableDeclaration(Variable),
 }
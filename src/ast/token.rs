@@ -1,8 +1,21 @@
-use crate::{ast::Identifier, interner::keywords::Keywords};
+use std::fmt;
+
+use crate::{
+    ast::{Decimal, Identifier, Integer},
+    interner::keywords::Keywords,
+    span::Span,
+};
+
+/// A token along with the range of source text it was lexed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
 
-#[derive(Debug, PartialEq)]
-pub enum Token<'a> {
-    Literal(Literal<'a>),
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Literal(Literal),
     Identifier(Identifier),
 
     Keyword(Keywords),
@@ -10,6 +23,10 @@ pub enum Token<'a> {
     /// Marks the potential end to an expression
     ExprEnd,
 
+    /// A line break which may end a statement. Line breaks inside `()` or
+    /// `[]`, after an operator or after another line break are not tokens
+    NewLine,
+
     /// (
     ParenOpen,
 
@@ -148,13 +165,6 @@ pub enum Token<'a> {
     /// ,
     Comma,
 
-    // TODO: lex everything beyond this point
-    /// <<<
-    UnsignedShl,
-
-    /// <<<=
-    UnsignedShlAssign,
-
     /// >>>
     UnsignedShr,
 
@@ -192,10 +202,25 @@ pub enum Token<'a> {
     SpreadMap,
 
     /// ..
-    ExclusiveRange,
+    InclusiveRange,
 
     /// ..<
-    InclusiveRange,
+    ExclusiveRange,
+
+    /// <..
+    ExclusiveLeftRange,
+
+    /// <..<
+    ExclusiveBothRange,
+
+    /// ...
+    Ellipsis,
+
+    /// ->
+    Arrow,
+
+    /// ?[
+    SafeIndex,
 
     /// <=>
     Spaceship,
@@ -212,25 +237,141 @@ pub enum Token<'a> {
     /// !instanceof
     NotInstanceOf,
 
-    /// \
-    IntegerDivision,
-
     /// ++
     PlusPlus,
 
     /// --
     MinusMinus,
 
+    /// The opening quotes of a string containing interpolations. The string
+    /// continues as a sequence of parts and interpolations up to the
+    /// matching `GStringEnd`
+    GStringStart,
+
+    /// A run of literal text within a gstring, with its escapes decoded
+    GStringPart(String),
+
+    /// The closing quotes of a gstring
+    GStringEnd,
+
+    /// $
+    /// The start of an interpolated property path within a gstring, such as
+    /// `$a.b`
+    Dollar,
+
     /// ${
     /// The start of an interpolated block within a gstring
     GStringInterpolationStart,
 }
 
-#[derive(Debug, PartialEq)]
-pub enum Literal<'a> {
-    String(&'a str),
-    Number(&'a str),
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Literal {
+    String(String),
+    Integer(Integer),
+    Decimal(Decimal),
     True,
     False,
     Null,
 }
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Token::Literal(lit) => return write!(f, "{}", lit),
+            Token::Identifier(ident) => return write!(f, "identifier `{}`", ident.name),
+            Token::Keyword(keyword) => return write!(f, "keyword `{}`", keyword.as_str()),
+            Token::ExprEnd => "`;`",
+            Token::NewLine => "line break",
+            Token::ParenOpen => "`(`",
+            Token::ParenClose => "`)`",
+            Token::CurlyBraceOpen => "`{`",
+            Token::CurlyBraceClose => "`}`",
+            Token::SquareBraceOpen => "`[`",
+            Token::SquareBraceClose => "`]`",
+            Token::Period => "`.`",
+            Token::SingleEqual => "`=`",
+            Token::LogicalNot => "`!`",
+            Token::Tilde => "`~`",
+            Token::Add => "`+`",
+            Token::AddAssign => "`+=`",
+            Token::Sub => "`-`",
+            Token::SubAssign => "`-=`",
+            Token::Mul => "`*`",
+            Token::MulAssign => "`*=`",
+            Token::Div => "`/`",
+            Token::DivAssign => "`/=`",
+            Token::Rem => "`%`",
+            Token::RemAssign => "`%=`",
+            Token::Pow => "`**`",
+            Token::PowAssign => "`**=`",
+            Token::Eq => "`==`",
+            Token::Ne => "`!=`",
+            Token::Lt => "`<`",
+            Token::Gt => "`>`",
+            Token::Le => "`<=`",
+            Token::Ge => "`>=`",
+            Token::Identical => "`===`",
+            Token::NotIdentical => "`!==`",
+            Token::LogicalAnd => "`&&`",
+            Token::LogicalOr => "`||`",
+            Token::BitwiseAnd => "`&`",
+            Token::BitwiseOr => "`|`",
+            Token::BitwiseAndAssign => "`&=`",
+            Token::BitwiseOrAssign => "`|=`",
+            Token::Xor => "`^`",
+            Token::XorAssign => "`^=`",
+            Token::Shl => "`<<`",
+            Token::Shr => "`>>`",
+            Token::ShlAssign => "`<<=`",
+            Token::ShrAssign => "`>>=`",
+            Token::Find => "`=~`",
+            Token::Match => "`==~`",
+            Token::BitwiseNotAssign => "`~=`",
+            Token::Comma => "`,`",
+            Token::UnsignedShr => "`>>>`",
+            Token::UnsignedShrAssign => "`>>>=`",
+            Token::QuestionMark => "`?`",
+            Token::Colon => "`:`",
+            Token::Elvis => "`?:`",
+            Token::ElvisAssignment => "`?=`",
+            Token::SafeNavigation => "`?.`",
+            Token::DirectFieldAccess => "`.@`",
+            Token::MethodPointer => "`.&`",
+            Token::MethodReference => "`::`",
+            Token::SpreadDot => "`*.`",
+            Token::SpreadMap => "`*:`",
+            Token::InclusiveRange => "`..`",
+            Token::ExclusiveRange => "`..<`",
+            Token::ExclusiveLeftRange => "`<..`",
+            Token::ExclusiveBothRange => "`<..<`",
+            Token::Ellipsis => "`...`",
+            Token::Arrow => "`->`",
+            Token::SafeIndex => "`?[`",
+            Token::Spaceship => "`<=>`",
+            Token::Diamond => "`<>`",
+            Token::AtSign => "`@`",
+            Token::NotIn => "`!in`",
+            Token::NotInstanceOf => "`!instanceof`",
+            Token::PlusPlus => "`++`",
+            Token::MinusMinus => "`--`",
+            Token::GStringStart | Token::GStringPart(..) => "string literal",
+            Token::GStringEnd => "end of string",
+            Token::Dollar => "`$`",
+            Token::GStringInterpolationStart => "`${`",
+        };
+        f.write_str(s)
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Literal::String(..) => f.write_str("string literal"),
+            Literal::Integer(n) => write!(f, "number `{}`", n),
+            Literal::Decimal(n) => write!(f, "number `{}`", n),
+            Literal::True => f.write_str("`true`"),
+            Literal::False => f.write_str("`false`"),
+            Literal::Null => f.write_str("`null`"),
+        }
+    }
+}
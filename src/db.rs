@@ -0,0 +1,349 @@
+//! The incremental analysis database. The text of each file is an input, and
+//! everything derived from it is a memoized query which salsa recomputes
+//! only when something it read has actually changed. Identifiers are
+//! interned per thread, so query results are shared with `Rc` and the
+//! database is never used from more than one thread
+
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    ast::Type,
+    diagnostic::{Diagnostic, ErrorCode},
+    items::{ClassPath, ItemSignatures},
+    lexer::{GroovyLexer, Lexed},
+    parse::{GroovyParser, Parse},
+    scope::Scopes,
+    span::{LineIndex, Span},
+};
+
+/// Identifies a file in the workspace. Ids are allocated by whoever sets the
+/// inputs and are never reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(pub u32);
+
+#[salsa::query_group(SourceDatabaseStorage)]
+pub trait SourceDatabase: salsa::Database {
+    #[salsa::input]
+    fn file_text(&self, file: FileId) -> Rc<String>;
+
+    /// Every file which takes part in analysis
+    #[salsa::input]
+    fn files(&self) -> Rc<Vec<FileId>>;
+
+    fn line_index(&self, file: FileId) -> Rc<LineIndex>;
+
+    fn tokens(&self, file: FileId) -> Rc<Lexed>;
+
+    fn parse(&self, file: FileId) -> Rc<Parse>;
+}
+
+fn line_index(db: &impl SourceDatabase, file: FileId) -> Rc<LineIndex> {
+    Rc::new(LineIndex::new(&db.file_text(file)))
+}
+
+fn tokens(db: &impl SourceDatabase, file: FileId) -> Rc<Lexed> {
+    Rc::new(GroovyLexer::tokenize(&db.file_text(file)))
+}
+
+fn parse(db: &impl SourceDatabase, file: FileId) -> Rc<Parse> {
+    let text = db.file_text(file);
+    let lexed = Lexed::clone(&db.tokens(file));
+    Rc::new(GroovyParser::parse_tokens(&text, lexed))
+}
+
+#[salsa::query_group(AnalysisDatabaseStorage)]
+pub trait AnalysisDatabase: SourceDatabase {
+    /// The declarations of a file as seen from other files. This is the only
+    /// query which cross-file analysis reads, so edits which do not change a
+    /// signature stop here
+    fn item_signatures(&self, file: FileId) -> Rc<ItemSignatures>;
+
+    /// Every class in the workspace by its package-qualified name, mapped to
+    /// the files declaring it
+    fn workspace_classes(&self) -> Rc<HashMap<ClassPath, Vec<FileId>>>;
+
+    fn scopes(&self, file: FileId) -> Rc<Scopes>;
+
+    fn diagnostics(&self, file: FileId) -> Rc<Vec<Diagnostic>>;
+}
+
+fn item_signatures(db: &impl AnalysisDatabase, file: FileId) -> Rc<ItemSignatures> {
+    Rc::new(ItemSignatures::new(&db.parse(file)))
+}
+
+fn workspace_classes(db: &impl AnalysisDatabase) -> Rc<HashMap<ClassPath, Vec<FileId>>> {
+    let mut classes = HashMap::<_, Vec<_>>::new();
+    for &file in db.files().iter() {
+        let signatures = db.item_signatures(file);
+        for class in &signatures.classes {
+            classes
+                .entry(signatures.path(class.name))
+                .or_default()
+                .push(file);
+        }
+    }
+    Rc::new(classes)
+}
+
+fn scopes(db: &impl AnalysisDatabase, file: FileId) -> Rc<Scopes> {
+    Rc::new(Scopes::new(&db.parse(file)))
+}
+
+fn diagnostics(db: &impl AnalysisDatabase, file: FileId) -> Rc<Vec<Diagnostic>> {
+    let parse = db.parse(file);
+    let mut diagnostics = parse.diagnostics.clone();
+
+    let classes = db.workspace_classes();
+    let own_signatures = db.item_signatures(file);
+    for class in &parse.unit.types {
+        let path = own_signatures.path(class.name.node);
+        let declared_in = classes.get(&path).map_or(1, Vec::len);
+        if declared_in > 1 {
+            diagnostics.push(Diagnostic::error(
+                ErrorCode::DuplicateDefinition,
+                class.name.span,
+                format!("class `{}` is defined more than once", class.name.node.name),
+            ));
+        }
+    }
+
+    // the classes a sealed type permits must actually extend it. Classes
+    // which are not declared in the workspace cannot be checked. A simple
+    // name refers to a class in the same package as the sealed type
+    for class in &parse.unit.types {
+        for permitted in &class.permits {
+            let Type::Class {
+                qualifier, name, ..
+            } = &permitted.node
+            else {
+                continue;
+            };
+            let name = *name;
+            let path = if qualifier.is_empty() {
+                own_signatures.path(name)
+            } else {
+                ClassPath {
+                    package: qualifier.clone(),
+                    name,
+                }
+            };
+            let signatures: Vec<_> = classes
+                .get(&path)
+                .into_iter()
+                .flatten()
+                .map(|&file| db.item_signatures(file))
+                .collect();
+            let mut declarations = signatures
+                .iter()
+                .flat_map(|signatures| &signatures.classes)
+                .filter(|signature| signature.name == name)
+                .peekable();
+            if declarations.peek().is_some()
+                && !declarations.any(|signature| signature.inherits_from(class.name.node))
+            {
+                diagnostics.push(Diagnostic::error(
+                    ErrorCode::InvalidPermittedSubclass,
+                    permitted.span,
+                    format!(
+                        "`{}` is permitted to extend sealed type `{}`, but does not",
+                        name.name, class.name.node.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    let scopes = db.scopes(file);
+    for (id, scope) in scopes.iter() {
+        for (i, entry) in scope.entries.iter().enumerate() {
+            let earlier = scope.entries[..i]
+                .iter()
+                .chain(
+                    scopes
+                        .ancestors(id)
+                        .skip(1)
+                        .flat_map(|parent| &scopes.get(parent).entries),
+                )
+                .find(|other| other.name == entry.name && other.span.start < entry.span.start);
+            if let Some(earlier) = earlier {
+                diagnostics.push(
+                    Diagnostic::error(
+                        ErrorCode::DuplicateDefinition,
+                        entry.span,
+                        format!("`{}` is already defined in this scope", entry.name.name),
+                    )
+                    .with_label(earlier.span, "previously defined here"),
+                );
+            }
+        }
+    }
+
+    Rc::new(diagnostics)
+}
+
+#[salsa::database(SourceDatabaseStorage, AnalysisDatabaseStorage)]
+#[derive(Default)]
+pub struct RootDatabase {
+    runtime: salsa::Runtime<RootDatabase>,
+}
+
+impl salsa::Database for RootDatabase {
+    fn salsa_runtime(&self) -> &salsa::Runtime<Self> {
+        &self.runtime
+    }
+
+    fn salsa_runtime_mut(&mut self) -> &mut salsa::Runtime<Self> {
+        &mut self.runtime
+    }
+}
+
+impl RootDatabase {
+    pub fn new() -> Self {
+        let mut db = Self::default();
+        db.set_files(Rc::new(Vec::new()));
+        db
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    /// A database which records the queries it executes
+    #[salsa::database(SourceDatabaseStorage, AnalysisDatabaseStorage)]
+    #[derive(Default)]
+    struct LoggingDatabase {
+        runtime: salsa::Runtime<LoggingDatabase>,
+        log: RefCell<Vec<String>>,
+    }
+
+    impl salsa::Database for LoggingDatabase {
+        fn salsa_runtime(&self) -> &salsa::Runtime<Self> {
+            &self.runtime
+        }
+
+        fn salsa_runtime_mut(&mut self) -> &mut salsa::Runtime<Self> {
+            &mut self.runtime
+        }
+
+        fn salsa_event(&self, event: impl Fn() -> salsa::Event<Self>) {
+            if let salsa::EventKind::WillExecute { database_key } = event().kind {
+                self.log.borrow_mut().push(format!("{:?}", database_key));
+            }
+        }
+    }
+
+    impl LoggingDatabase {
+        fn executed(&self) -> Vec<String> {
+            self.log.borrow_mut().drain(..).collect()
+        }
+    }
+
+    const A: FileId = FileId(0);
+    const B: FileId = FileId(1);
+
+    fn workspace(a: &str, b: &str) -> LoggingDatabase {
+        let mut db = LoggingDatabase::default();
+        db.set_file_text(A, Rc::new(a.to_owned()));
+        db.set_file_text(B, Rc::new(b.to_owned()));
+        db.set_files(Rc::new(vec![A, B]));
+        db
+    }
+
+    #[test]
+    fn editing_a_method_body_only_reanalyzes_that_file() {
+        let mut db = workspace(
+            "class A { int f(int a) { return 1 } }",
+            "class B { int g(int b) { return 2 } }",
+        );
+        db.diagnostics(A);
+        db.diagnostics(B);
+        db.executed();
+
+        db.set_file_text(
+            A,
+            Rc::new("class A { int f(int a) { return 1 + 2 } }".to_owned()),
+        );
+        assert!(db.diagnostics(A).is_empty());
+        assert!(db.diagnostics(B).is_empty());
+
+        let executed = db.executed().join("\n");
+        assert!(executed.contains("parse(FileId(0))"), "{}", executed);
+        assert!(!executed.contains("FileId(1)"), "{}", executed);
+        assert!(!executed.contains("workspace_classes"), "{}", executed);
+    }
+
+    #[test]
+    fn duplicate_classes_are_reported_in_every_file() {
+        let mut db = workspace("class A {}", "class A {}");
+        assert_eq!(db.diagnostics(A)[0].code, ErrorCode::DuplicateDefinition);
+        assert_eq!(db.diagnostics(B)[0].span, Span::new(6, 7));
+
+        db.set_file_text(B, Rc::new("class B {}".to_owned()));
+        assert!(db.diagnostics(A).is_empty());
+    }
+
+    #[test]
+    fn classes_in_different_packages_are_distinct() {
+        let mut db = workspace("package a\nclass Util {}", "package b\nclass Util {}");
+        assert!(db.diagnostics(A).is_empty(), "{:?}", db.diagnostics(A));
+        assert!(db.diagnostics(B).is_empty());
+
+        db.set_file_text(B, Rc::new("package a\nclass Util {}".to_owned()));
+        assert_eq!(db.diagnostics(A)[0].code, ErrorCode::DuplicateDefinition);
+    }
+
+    #[test]
+    fn permitted_subclasses_are_looked_up_by_package() {
+        let mut db = workspace(
+            "package shapes\nsealed class Shape permits Circle, other.Square {}",
+            "package other\nclass Circle {}\nclass Square extends shapes.Shape {}",
+        );
+        // `Circle` means `shapes.Circle`, which is not in the workspace
+        assert!(db.diagnostics(A).is_empty(), "{:?}", db.diagnostics(A));
+
+        db.set_file_text(
+            B,
+            Rc::new("package other\nclass Circle {}\nclass Square {}".to_owned()),
+        );
+        let diagnostics = db.diagnostics(A);
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].code, ErrorCode::InvalidPermittedSubclass);
+    }
+
+    #[test]
+    fn permitted_subclasses_must_extend_the_sealed_type() {
+        let mut db = workspace(
+            "sealed class Shape permits Circle, Square, External {}",
+            "class Circle extends Shape {}\nclass Square {}",
+        );
+        let diagnostics = db.diagnostics(A);
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].code, ErrorCode::InvalidPermittedSubclass);
+        assert_eq!(diagnostics[0].span, Span::new(35, 41));
+
+        db.set_file_text(
+            B,
+            Rc::new("class Circle extends Shape {}\nclass Square extends Shape {}".to_owned()),
+        );
+        assert!(db.diagnostics(A).is_empty());
+    }
+
+    #[test]
+    fn redeclared_locals_are_reported() {
+        let input = "class A {\n  int f(int a) {\n    int a = 1\n    { int b = 2 }\n    int b = 3\n  }\n}\n";
+        let db = workspace(input, "");
+        let diagnostics = db.diagnostics(A);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`a` is already defined in this scope"
+        );
+        assert_eq!(
+            diagnostics[0].labels[0].span.start,
+            input.find("a)").unwrap()
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Errors and warnings reported about Groovy source, independent of how they
+//! are eventually presented to the user
+
+use std::fmt;
+
+use crate::span::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+/// A stable identifier for each kind of diagnostic, so that clients can link
+/// to documentation or filter on them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// A token appeared where the grammar does not allow it
+    UnexpectedToken,
+    /// A required part of a construct was missing, e.g. the name of a class
+    MissingSyntax,
+    /// A `(`, `[` or `{` was never closed, or a `/*` comment never ended
+    UnclosedDelimiter,
+    /// A `)`, `]` or `}` does not correspond to any opening delimiter
+    UnmatchedDelimiter,
+    /// A character which cannot begin any token
+    UnknownCharacter,
+    /// Valid Groovy which this implementation cannot handle yet
+    Unsupported,
+    /// A name declared twice where only one declaration is allowed
+    DuplicateDefinition,
+    /// A string literal which is not closed before the end of its line, or
+    /// of the file
+    UnterminatedString,
+    /// A backslash in a string followed by something other than a valid
+    /// escape
+    InvalidEscape,
+    /// A numeric literal which is malformed or too large for its type
+    InvalidNumber,
+    /// A class named in `permits` which does not extend the sealed type
+    InvalidPermittedSubclass,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedToken => "E0001",
+            ErrorCode::MissingSyntax => "E0002",
+            ErrorCode::UnclosedDelimiter => "E0003",
+            ErrorCode::UnmatchedDelimiter => "E0004",
+            ErrorCode::UnknownCharacter => "E0005",
+            ErrorCode::Unsupported => "E0006",
+            ErrorCode::DuplicateDefinition => "E0007",
+            ErrorCode::UnterminatedString => "E0008",
+            ErrorCode::InvalidEscape => "E0009",
+            ErrorCode::InvalidNumber => "E0010",
+            ErrorCode::InvalidPermittedSubclass => "E0011",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An additional location relevant to a diagnostic, such as the opening brace
+/// of an unclosed block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A proposed edit which would resolve a diagnostic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: ErrorCode,
+    pub message: String,
+    pub span: Span,
+    pub labels: Vec<Label>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        code: ErrorCode,
+        span: Span,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.into(),
+            span,
+            labels: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    pub fn error(code: ErrorCode, span: Span, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, code, span, message)
+    }
+
+    pub fn warning(code: ErrorCode, span: Span, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, code, span, message)
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_suggestion(
+        mut self,
+        message: impl Into<String>,
+        span: Span,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.suggestions.push(Suggestion {
+            message: message.into(),
+            span,
+            replacement: replacement.into(),
+        });
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Information => "info",
+            Severity::Hint => "hint",
+        };
+        write!(f, "{}[{}]: {}", severity, self.code, self.message)
+    }
+}
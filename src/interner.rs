@@ -59,7 +59,7 @@ impl fmt::Display for Symbol {
     }
 }
 
-impl<'a> PartialEq<&'a str> for Symbol {
+impl PartialEq<&str> for Symbol {
     fn eq(&self, other: &&str) -> bool {
         *self.as_str() == **other
     }
@@ -124,6 +124,16 @@ macro_rules! declare_keywords {(
             )*
         }
 
+        impl Keywords {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(
+                        Self::$konst => $string,
+                    )*
+                }
+            }
+        }
+
         impl TryFrom<ast::Identifier> for Keywords {
             type Error = ast::Identifier;
             fn try_from(value: ast::Identifier) -> Result<Self, Self::Error> {
@@ -204,29 +214,50 @@ declare_keywords! {
     (33, Long,         "long")
     (34, Native,       "native")
     (35, New,          "new")
-    (36, Null,         "null")
-    (37, Package,      "package")
-    (38, Private,      "private")
-    (39, Protected,    "protected")
-    (40, Public,       "public")
-    (41, Return,       "return")
-    (42, Short,        "short")
-    (43, Static,       "static")
-    (44, StrictFp,     "strictfp")
-    (45, Super,        "super")
-    (46, Switch,       "switch")
-    (47, Synchronized, "synchronized")
-    (48, This,         "this")
-    (49, ThreadSafe,   "threadsafe")
-    (50, Throw,        "throw")
-    (51, Throws,       "throws")
-    (52, Transient,    "transient")
-    (53, True,         "true")
-    (54, Try,          "try")
-    (55, Var,          "var")
-    (56, Void,         "void")
-    (57, Volatile,     "volatile")
-    (58, While,        "while")
+    (36, NonSealed,    "non-sealed")
+    (37, Null,         "null")
+    (38, Package,      "package")
+    (39, Permits,      "permits")
+    (40, Private,      "private")
+    (41, Protected,    "protected")
+    (42, Public,       "public")
+    (43, Record,       "record")
+    (44, Return,       "return")
+    (45, Sealed,       "sealed")
+    (46, Short,        "short")
+    (47, Static,       "static")
+    (48, StrictFp,     "strictfp")
+    (49, Super,        "super")
+    (50, Switch,       "switch")
+    (51, Synchronized, "synchronized")
+    (52, This,         "this")
+    (53, ThreadSafe,   "threadsafe")
+    (54, Throw,        "throw")
+    (55, Throws,       "throws")
+    (56, Trait,        "trait")
+    (57, Transient,    "transient")
+    (58, True,         "true")
+    (59, Try,          "try")
+    (60, Var,          "var")
+    (61, Void,         "void")
+    (62, Volatile,     "volatile")
+    (63, While,        "while")
+}
+
+impl keywords::Keywords {
+    /// Keywords which only have a special meaning in certain positions, and
+    /// are otherwise ordinary identifiers. They are lexed as identifiers, and
+    /// the parser recognizes them by name where they are allowed
+    pub fn is_contextual(self) -> bool {
+        matches!(
+            self,
+            keywords::Keywords::NonSealed
+                | keywords::Keywords::Permits
+                | keywords::Keywords::Record
+                | keywords::Keywords::Sealed
+                | keywords::Keywords::Trait
+        )
+    }
 }
 
 // If an interner exists in TLS, return it. Otherwise, prepare a fresh one.
@@ -234,7 +265,7 @@ fn with_interner<T, F: FnOnce(&mut Interner) -> T>(f: F) -> T {
     thread_local!(static INTERNER: RefCell<Interner> = {
         RefCell::new(Interner::fresh())
     });
-    INTERNER.with(|interner| f(&mut *interner.borrow_mut()))
+    INTERNER.with(|interner| f(&mut interner.borrow_mut()))
 }
 
 /// Represents a string stored in the thread-local interner. Because the
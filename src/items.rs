@@ -0,0 +1,192 @@
+//! The externally visible shape of the declarations in a file, stripped of
+//! spans and bodies. Other files only ever depend on these signatures, so an
+//! edit which leaves them unchanged does not invalidate anything outside the
+//! edited file
+
+use crate::{
+    ast::{
+        Class, ClassKind, ClassModifier, Field, Identifier, Method, MethodModifier, Type,
+        Visibility,
+    },
+    parse::Parse,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemSignatures {
+    /// The segments of the package declaration, empty for the default
+    /// package
+    pub package: Vec<Identifier>,
+    pub classes: Vec<ClassSignature>,
+}
+
+/// A class name qualified by the package declaring it. Classes with the same
+/// simple name in different packages are distinct
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassPath {
+    pub package: Vec<Identifier>,
+    pub name: Identifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassSignature {
+    pub name: Identifier,
+    pub kind: ClassKind,
+    pub visibility: Visibility,
+    pub modifiers: Vec<ClassModifier>,
+    pub type_parameters: Vec<TypeParameterSignature>,
+    pub super_class: Option<Type>,
+    pub interfaces: Vec<Type>,
+    pub fields: Vec<FieldSignature>,
+    pub properties: Vec<FieldSignature>,
+    pub methods: Vec<MethodSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSignature {
+    pub name: Identifier,
+    pub visibility: Visibility,
+    pub modifiers: Vec<MethodModifier>,
+    pub field_type: Type,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeParameterSignature {
+    pub name: Identifier,
+    pub bounds: Vec<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodSignature {
+    pub name: Identifier,
+    pub visibility: Visibility,
+    pub modifiers: Vec<MethodModifier>,
+    pub return_type: Type,
+    pub parameters: Vec<ParameterSignature>,
+    pub exceptions: Vec<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterSignature {
+    pub name: Identifier,
+    pub param_type: Type,
+    /// Whether the argument can be left out. Only the presence of a default
+    /// matters to callers, not its value
+    pub has_default_value: bool,
+    pub is_varargs: bool,
+}
+
+impl ItemSignatures {
+    pub fn new(parse: &Parse) -> Self {
+        let package = parse
+            .unit
+            .package
+            .as_ref()
+            .map_or_else(Vec::new, |package| {
+                package
+                    .name
+                    .segments
+                    .iter()
+                    .map(|segment| segment.node)
+                    .collect()
+            });
+        let classes = parse.unit.types.iter().map(ClassSignature::new).collect();
+        Self { package, classes }
+    }
+
+    /// The qualified name of a class declared in this file
+    pub fn path(&self, name: Identifier) -> ClassPath {
+        ClassPath {
+            package: self.package.clone(),
+            name,
+        }
+    }
+}
+
+impl ClassSignature {
+    fn new(class: &Class) -> Self {
+        Self {
+            name: class.name.node,
+            kind: class.kind,
+            visibility: class.visibility,
+            modifiers: class.modifiers.clone(),
+            type_parameters: class
+                .type_parameters
+                .iter()
+                .map(|param| TypeParameterSignature {
+                    name: param.name.node,
+                    bounds: param.bounds.iter().map(|ty| ty.node.clone()).collect(),
+                })
+                .collect(),
+            super_class: class.super_class.as_ref().map(|ty| ty.node.clone()),
+            interfaces: class.interfaces.iter().map(|ty| ty.node.clone()).collect(),
+            fields: class.fields.iter().map(FieldSignature::new).collect(),
+            properties: class
+                .properties
+                .iter()
+                .map(|property| FieldSignature::new(&property.field))
+                .collect(),
+            methods: class.methods.iter().map(MethodSignature::new).collect(),
+        }
+    }
+
+    /// Whether the class directly extends or implements the type `name`
+    pub fn inherits_from(&self, name: Identifier) -> bool {
+        self.super_class
+            .iter()
+            .chain(&self.interfaces)
+            .any(|ty| ty.class_name() == Some(name))
+    }
+}
+
+impl FieldSignature {
+    fn new(field: &Field) -> Self {
+        Self {
+            name: field.name.node,
+            visibility: field.visibility,
+            modifiers: field.modifiers.clone(),
+            field_type: field.field_type.clone(),
+        }
+    }
+}
+
+impl MethodSignature {
+    fn new(method: &Method) -> Self {
+        Self {
+            name: method.name.node,
+            visibility: method.visibility,
+            modifiers: method.modifiers.clone(),
+            return_type: method.return_type.clone(),
+            parameters: method
+                .parameters
+                .iter()
+                .map(|param| ParameterSignature {
+                    name: param.name.node,
+                    param_type: param.param_type.clone(),
+                    has_default_value: param.default_value.is_some(),
+                    is_varargs: param.is_varargs,
+                })
+                .collect(),
+            exceptions: method
+                .exceptions
+                .iter()
+                .map(|exception| exception.node.clone())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::GroovyParser;
+
+    #[test]
+    fn signatures_ignore_bodies_and_layout() {
+        let a = GroovyParser::parse("class A {\n  int f(int a) {\n    return 1\n  }\n}\n");
+        let b = GroovyParser::parse("class A { int f(int a) { return 1 + 2 } }");
+        assert_eq!(ItemSignatures::new(&a), ItemSignatures::new(&b));
+
+        let c = GroovyParser::parse("class A { int f(long a) { return 1 } }");
+        assert_ne!(ItemSignatures::new(&a), ItemSignatures::new(&c));
+    }
+}
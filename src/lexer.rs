@@ -1,17 +1,39 @@
-use std::convert::TryFrom;
+use std::{collections::VecDeque, convert::TryFrom};
 
 use crate::{
-    ast::{Identifier, Literal, Token},
+    ast::{Decimal, Identifier, Integer, Literal, SpannedToken, Token},
+    diagnostic::{Diagnostic, ErrorCode},
     interner::keywords::Keywords,
+    span::{Span, Spanned},
 };
 
-// todo: we must store the last token in order to resolve ambiguities with
-// `/` (is it a slashy-regex string or division?)
+/// The tokens of a file, along with any errors found while lexing them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexed {
+    pub tokens: Vec<SpannedToken>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// The text of each `/** */` comment, in order
+    pub doc_comments: Vec<Spanned<String>>,
+}
+
 pub(crate) struct GroovyLexer<'a> {
     input: &'a str,
     input_len: usize,
     start: usize,
     pos: usize,
+    diagnostics: Vec<Diagnostic>,
+    doc_comments: Vec<Spanned<String>>,
+    /// Tokens which have already been lexed but not yet returned, as happens
+    /// when a whole gstring is lexed at once
+    pending: VecDeque<SpannedToken>,
+    /// Whether a `/` at this point would begin a slashy string rather than
+    /// being a division, which depends on the previous token
+    regex_allowed: bool,
+    /// Whether the previous token could end a statement, so that a line
+    /// break after it is significant
+    newline_allowed: bool,
+    /// The opening `(`, `[` and `{` which have not been closed yet
+    open_brackets: Vec<Token>,
 }
 
 impl<'a> GroovyLexer<'a> {
@@ -21,51 +43,134 @@ impl<'a> GroovyLexer<'a> {
             input_len: s.len(),
             start: 0,
             pos: 0,
+            diagnostics: Vec::new(),
+            doc_comments: Vec::new(),
+            pending: VecDeque::new(),
+            regex_allowed: true,
+            newline_allowed: false,
+            open_brackets: Vec::new(),
+        }
+    }
+
+    /// Lexes the whole of `input`
+    pub fn tokenize(input: &'a str) -> Lexed {
+        let mut lexer = Self::new(input);
+        let tokens = lexer.by_ref().collect();
+        Lexed {
+            tokens,
+            diagnostics: lexer.diagnostics,
+            doc_comments: lexer.doc_comments,
         }
     }
 
-    pub fn lex(&mut self) -> Token<'a> {
-        self.whitespace();
-        match self.next_char() {
-            Some(
-                'a'..='z'
-                | 'A'..='Z'
-                | '\u{00C0}'..='\u{00D6}'
-                | '\u{00D8}'..='\u{00F6}'
-                | '\u{00F8}'..='\u{00FF}'
-                | '\u{0100}'..='\u{FFFE}'
-                | '_',
-            ) => self.lex_identifier(),
-            Some('0'..='9') => self.lex_number(),
-            Some('+') => self.lex_plus(),
-            Some('-') => self.lex_minus(),
-            Some('*') => self.lex_mul(),
-            Some('/') => self.lex_div(),
-            Some('%') => self.lex_percent(),
-            Some('=') => self.lex_equal(),
-            Some('!') => self.lex_exclamation(),
-            Some('<') => self.lex_less_than(),
-            Some('>') => self.lex_greater_than(),
-            Some('&') => self.lex_ampersand(),
-            Some('|') => self.lex_pipe(),
-            Some('^') => self.lex_caret(),
-            Some('~') => self.lex_tilde(),
-            Some('.') => Token::Period,
-            Some('(') => Token::ParenOpen,
-            Some(')') => Token::ParenClose,
-            Some('{') => Token::CurlyBraceOpen,
-            Some('}') => Token::CurlyBraceClose,
-            Some('[') => Token::SquareBraceOpen,
-            Some(']') => Token::SquareBraceClose,
-            Some(',') => Token::Comma,
-            Some('\'') => todo!("lex single quote"),
-            Some('"') => todo!("lex double quote"),
-            Some('?') => todo!("lex question mark"),
-            Some(':') => todo!("lex colon"),
-            Some('$') => todo!("could be start of identifier *or* string, `$/.../$`"),
-            Some(';') => Token::ExprEnd,
-            c => todo!("lex {:?}", c),
+    /// Lexes the next token, returning `None` once the input is exhausted
+    pub fn lex(&mut self) -> Option<SpannedToken> {
+        let tok = loop {
+            if let Some(tok) = self.pending.pop_front() {
+                break tok;
+            }
+            if let Some(newline) = self.whitespace() {
+                break newline;
+            }
+            let c = self.next_char()?;
+            if let Some(token) = self.lex_token(c) {
+                break SpannedToken {
+                    token,
+                    span: Span::new(self.start, self.pos),
+                };
+            }
+        };
+        self.regex_allowed = !ends_operand(&tok.token);
+        self.newline_allowed = !continues_statement(&tok.token);
+        match tok.token {
+            Token::ParenOpen
+            | Token::SquareBraceOpen
+            | Token::SafeIndex
+            | Token::CurlyBraceOpen
+            | Token::GStringInterpolationStart => self.open_brackets.push(tok.token.clone()),
+            Token::ParenClose | Token::SquareBraceClose | Token::CurlyBraceClose => {
+                self.open_brackets.pop();
+            }
+            _ => {}
         }
+        Some(tok)
+    }
+
+    /// Lexes the token beginning with `c`. Returns `None` if `c` began a
+    /// comment or cannot begin any token, in which case lexing should
+    /// continue after it, or if the tokens were queued in `pending` instead
+    fn lex_token(&mut self, c: char) -> Option<Token> {
+        Some(match c {
+            '$' if self.peek_char() == Some('/') => {
+                self.next_char();
+                return self.lex_string(StringKind::DollarSlashy);
+            }
+            'a'..='z'
+            | 'A'..='Z'
+            | '\u{00C0}'..='\u{00D6}'
+            | '\u{00D8}'..='\u{00F6}'
+            | '\u{00F8}'..='\u{00FF}'
+            | '\u{0100}'..='\u{FFFE}'
+            | '_'
+            | '$' => self.lex_identifier(),
+            '0'..='9' => self.lex_number(),
+            '+' => self.lex_plus(),
+            '-' => self.lex_minus(),
+            '*' => self.lex_mul(),
+            '/' => return self.lex_div(),
+            '\'' | '"' => {
+                let triple = self.input.as_bytes()[self.pos..].starts_with(&[c as u8; 2]);
+                let kind = match (c, triple) {
+                    ('\'', false) => StringKind::Single,
+                    ('\'', true) => StringKind::TripleSingle,
+                    (_, false) => StringKind::Double,
+                    (_, true) => StringKind::TripleDouble,
+                };
+                if triple {
+                    self.pos += 2;
+                }
+                return self.lex_string(kind);
+            }
+            '%' => self.lex_percent(),
+            '=' => self.lex_equal(),
+            '!' => self.lex_exclamation(),
+            '<' => self.lex_less_than(),
+            '>' => self.lex_greater_than(),
+            '&' => self.lex_ampersand(),
+            '|' => self.lex_pipe(),
+            '^' => self.lex_caret(),
+            '~' => self.lex_tilde(),
+            '.' => self.lex_period(),
+            '(' => Token::ParenOpen,
+            ')' => Token::ParenClose,
+            '{' => Token::CurlyBraceOpen,
+            '}' => Token::CurlyBraceClose,
+            '[' => Token::SquareBraceOpen,
+            ']' => Token::SquareBraceClose,
+            ',' => Token::Comma,
+            '?' => self.lex_question_mark(),
+            ':' => self.lex_colon(),
+            '@' => Token::AtSign,
+            ';' => Token::ExprEnd,
+            // a shebang line, as in `#!/usr/bin/env groovy`, is only
+            // allowed at the very start of the file
+            '#' if self.start == 0 && self.peek_char() == Some('!') => {
+                while let Some(c) = self.next_char() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                return None;
+            }
+            c => {
+                self.diagnostics.push(Diagnostic::error(
+                    ErrorCode::UnknownCharacter,
+                    Span::new(self.start, self.pos),
+                    format!("unknown character `{}`", c.escape_debug()),
+                ));
+                return None;
+            }
+        })
     }
 
     /// 3.1. Normal identifiers
@@ -86,46 +191,51 @@ impl<'a> GroovyLexer<'a> {
     ///     '\u{0100}' to '\u{FFFE}'
     ///
     /// Then following characters can contain letters and numbers.
-    fn lex_identifier(&mut self) -> Token<'a> {
-        while let Some(
-            'a'..='z'
-            | 'A'..='Z'
-            | '\u{00C0}'..='\u{00D6}'
-            | '\u{00D8}'..='\u{00F6}'
-            | '\u{00F8}'..='\u{00FF}'
-            | '\u{0100}'..='\u{FFFE}'
-            | '_'
-            | '0'..='9',
-        ) = self.peek_char()
-        {
+    fn lex_identifier(&mut self) -> Token {
+        while self.peek_char().is_some_and(is_identifier_part) {
             self.next_char();
         }
-
-        match Keywords::try_from(Identifier::new(&self.input[self.start..self.pos])) {
-            Ok(keyword) => Token::Keyword(keyword),
-            Err(ident) => Token::Identifier(ident),
+        // the only keyword containing something other than letters
+        if &self.input[self.start..self.pos] == "non" {
+            self.eat_keyword("-sealed");
         }
+
+        identifier_or_keyword(&self.input[self.start..self.pos])
     }
 
-    fn lex_plus(&mut self) -> Token<'a> {
-        if self.peek_char() == Some('=') {
-            self.next_char();
-            Token::AddAssign
-        } else {
-            Token::Add
+    fn lex_plus(&mut self) -> Token {
+        match self.peek_char() {
+            Some('=') => {
+                self.next_char();
+                Token::AddAssign
+            }
+            Some('+') => {
+                self.next_char();
+                Token::PlusPlus
+            }
+            _ => Token::Add,
         }
     }
 
-    fn lex_minus(&mut self) -> Token<'a> {
-        if self.peek_char() == Some('=') {
-            self.next_char();
-            Token::SubAssign
-        } else {
-            Token::Sub
+    fn lex_minus(&mut self) -> Token {
+        match self.peek_char() {
+            Some('=') => {
+                self.next_char();
+                Token::SubAssign
+            }
+            Some('-') => {
+                self.next_char();
+                Token::MinusMinus
+            }
+            Some('>') => {
+                self.next_char();
+                Token::Arrow
+            }
+            _ => Token::Sub,
         }
     }
 
-    fn lex_mul(&mut self) -> Token<'a> {
+    fn lex_mul(&mut self) -> Token {
         match self.peek_char() {
             Some('=') => {
                 self.next_char();
@@ -140,30 +250,126 @@ impl<'a> GroovyLexer<'a> {
                     Token::Pow
                 }
             }
+            Some('.') => {
+                self.next_char();
+                Token::SpreadDot
+            }
+            Some(':') => {
+                self.next_char();
+                Token::SpreadMap
+            }
             _ => Token::Mul,
         }
     }
 
-    fn lex_div(&mut self) -> Token<'a> {
+    fn lex_period(&mut self) -> Token {
+        if self.eat("..") {
+            Token::Ellipsis
+        } else if self.eat(".<") {
+            Token::ExclusiveRange
+        } else if self.eat(".") {
+            Token::InclusiveRange
+        } else if self.eat("@") {
+            Token::DirectFieldAccess
+        } else if self.eat("&") {
+            Token::MethodPointer
+        } else {
+            Token::Period
+        }
+    }
+
+    fn lex_question_mark(&mut self) -> Token {
         match self.peek_char() {
+            Some('.') => {
+                self.next_char();
+                Token::SafeNavigation
+            }
+            Some(':') => {
+                self.next_char();
+                Token::Elvis
+            }
             Some('=') => {
                 self.next_char();
-                Token::DivAssign
+                Token::ElvisAssignment
+            }
+            Some('[') => {
+                self.next_char();
+                Token::SafeIndex
+            }
+            _ => Token::QuestionMark,
+        }
+    }
+
+    fn lex_colon(&mut self) -> Token {
+        if self.peek_char() == Some(':') {
+            self.next_char();
+            Token::MethodReference
+        } else {
+            Token::Colon
+        }
+    }
+
+    fn lex_div(&mut self) -> Option<Token> {
+        Some(match self.peek_char() {
+            Some('*') => {
+                self.lex_block_comment();
+                return None;
             }
-            Some('*') => todo!("multiline comments"),
             Some('/') => {
-                while let Some(tok) = self.next_char() {
-                    if tok == '\n' {
-                        break;
-                    }
+                // the line break is left for `whitespace`, as it may be
+                // significant
+                while self.peek_char().is_some_and(|c| c != '\n') {
+                    self.next_char();
                 }
-                self.lex()
+                return None;
+            }
+            _ if self.regex_allowed => return self.lex_string(StringKind::Slashy),
+            Some('=') => {
+                self.next_char();
+                Token::DivAssign
             }
             _ => Token::Div,
+        })
+    }
+
+    /// Skips a `/* */` comment whose `/` has been consumed, keeping the text
+    /// of Groovydoc `/** */` comments
+    fn lex_block_comment(&mut self) {
+        self.next_char();
+        let is_doc =
+            self.input[self.pos..].starts_with('*') && !self.input[self.pos..].starts_with("*/");
+        let text_start = self.pos;
+        let text_end = match self.input[self.pos..].find("*/") {
+            Some(len) => {
+                self.pos += len + 2;
+                self.pos - 2
+            }
+            None => {
+                self.pos = self.input_len;
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        ErrorCode::UnclosedDelimiter,
+                        Span::new(self.start, self.pos),
+                        "unterminated block comment",
+                    )
+                    .with_suggestion(
+                        "insert the missing `*/`",
+                        Span::empty(self.pos),
+                        "*/",
+                    ),
+                );
+                self.pos
+            }
+        };
+
+        if is_doc {
+            let text = doc_comment_text(&self.input[text_start + 1..text_end]);
+            self.doc_comments
+                .push(Spanned::new(text, Span::new(self.start, self.pos)));
         }
     }
 
-    fn lex_percent(&mut self) -> Token<'a> {
+    fn lex_percent(&mut self) -> Token {
         if self.peek_char() == Some('=') {
             self.next_char();
             Token::RemAssign
@@ -172,7 +378,7 @@ impl<'a> GroovyLexer<'a> {
         }
     }
 
-    fn lex_ampersand(&mut self) -> Token<'a> {
+    fn lex_ampersand(&mut self) -> Token {
         match self.peek_char() {
             Some('&') => {
                 self.next_char();
@@ -186,7 +392,7 @@ impl<'a> GroovyLexer<'a> {
         }
     }
 
-    fn lex_pipe(&mut self) -> Token<'a> {
+    fn lex_pipe(&mut self) -> Token {
         match self.peek_char() {
             Some('|') => {
                 self.next_char();
@@ -200,28 +406,502 @@ impl<'a> GroovyLexer<'a> {
         }
     }
 
-    fn lex_number(&mut self) -> Token<'a> {
-        while let Some('0'..='9') = self.peek_char() {
+    /// Lexes any Groovy number: decimal, hex (`0x`), binary (`0b`) and octal
+    /// (leading `0`) integers, decimals with fractions and exponents, and
+    /// underscores between digits, followed by an optional type suffix.
+    /// Unsuffixed integers take the smallest of `int`, `long` and
+    /// `BigInteger` which holds them, and unsuffixed decimals are
+    /// `BigDecimal`
+    fn lex_number(&mut self) -> Token {
+        let mut radix = 10;
+        if &self.input[self.start..self.pos] == "0" {
+            match self.peek_char() {
+                Some('x' | 'X') => radix = 16,
+                Some('b' | 'B') => radix = 2,
+                _ => {}
+            }
+        }
+        let digits_start = if radix == 10 {
+            self.start
+        } else {
+            self.next_char();
+            self.pos
+        };
+        self.eat_digits(radix);
+
+        let mut is_decimal = false;
+        if radix == 10 {
+            let mut rest = self.input[self.pos..].chars();
+            // `1.foo()` and `1..2` are not decimals
+            if let (Some('.'), Some('0'..='9')) = (rest.next(), rest.next()) {
+                self.next_char();
+                self.eat_digits(10);
+                is_decimal = true;
+            }
+            let mut rest = self.input[self.pos..].chars();
+            if let (Some('e' | 'E'), Some(c)) = (rest.next(), rest.next()) {
+                let signed = matches!(c, '+' | '-');
+                if c.is_ascii_digit() || (signed && matches!(rest.next(), Some('0'..='9'))) {
+                    self.next_char();
+                    if signed {
+                        self.next_char();
+                    }
+                    self.eat_digits(10);
+                    is_decimal = true;
+                }
+            }
+        }
+        let digits_end = self.pos;
+
+        let suffix = match self.peek_char() {
+            Some(c @ ('i' | 'I' | 'l' | 'L' | 'g' | 'G')) => Some(c.to_ascii_lowercase()),
+            // these are hex digits, so they never follow a hex literal
+            Some(c @ ('f' | 'F' | 'd' | 'D')) if radix == 10 => Some(c.to_ascii_lowercase()),
+            _ => None,
+        };
+        if suffix.is_some() {
+            self.next_char();
+        }
+
+        let raw = &self.input[digits_start..digits_end];
+        let mut digits: String = raw.chars().filter(|&c| c != '_').collect();
+
+        // a run of letters or digits straight after the number, as in `12abc`
+        let trailing_start = self.pos;
+        while let Some(c) = self.peek_char() {
+            if !(c.is_alphanumeric() || c == '_' || c == '$') {
+                break;
+            }
             self.next_char();
         }
-        Token::Literal(Literal::Number(&self.input[self.start..self.pos]))
+        if self.pos > trailing_start {
+            self.number_error(format!(
+                "invalid suffix `{}` on numeric literal",
+                &self.input[trailing_start..self.pos]
+            ));
+        } else if !underscores_between_digits(raw, radix) {
+            self.number_error("`_` may only appear between digits");
+        } else if radix != 10 && digits.is_empty() {
+            let base = if radix == 16 { "hex" } else { "binary" };
+            self.number_error(format!("{} literal has no digits", base));
+            digits.push('0');
+        }
+
+        if is_decimal || matches!(suffix, Some('f' | 'd')) {
+            return Token::Literal(Literal::Decimal(
+                self.decimal_value(digits, is_decimal, suffix),
+            ));
+        }
+
+        if radix == 10 && digits.len() > 1 && digits.starts_with('0') {
+            radix = 8;
+            if let Some(c) = digits.chars().find(|&c| c == '8' || c == '9') {
+                self.number_error(format!("invalid digit `{}` in octal literal", c));
+                digits.retain(|c| c != '8' && c != '9');
+            }
+        }
+        Token::Literal(Literal::Integer(self.integer_value(&digits, radix, suffix)))
     }
 
-    fn lex_exclamation(&mut self) -> Token<'a> {
-        if self.peek_char() == Some('=') {
-            self.next();
-            if self.peek_char() == Some('=') {
-                self.next();
-                Token::NotIdentical
-            } else {
-                Token::Ne
+    /// Consumes digits of the given radix along with any underscores
+    fn eat_digits(&mut self, radix: u32) {
+        while let Some(c) = self.peek_char() {
+            if !(c.is_digit(radix) || c == '_') {
+                break;
+            }
+            self.next_char();
+        }
+    }
+
+    fn integer_value(&mut self, digits: &str, radix: u32, suffix: Option<char>) -> Integer {
+        let value = digits.chars().try_fold(0_u64, |acc, c| {
+            acc.checked_mul(radix as u64)?
+                .checked_add(c.to_digit(radix).unwrap() as u64)
+        });
+        let big = || Integer::BigInteger(to_decimal_string(digits, radix));
+
+        match (suffix, value) {
+            (Some('g'), _) => big(),
+            (Some('i'), Some(n)) if n <= i32::MAX as u64 => Integer::Int(n as i32),
+            (Some('i'), _) => {
+                self.number_error("integer literal is too large for `int`");
+                big()
+            }
+            (Some('l'), Some(n)) if n <= i64::MAX as u64 => Integer::Long(n as i64),
+            (Some('l'), _) => {
+                self.number_error("integer literal is too large for `long`");
+                big()
+            }
+            (_, Some(n)) if n <= i32::MAX as u64 => Integer::Int(n as i32),
+            (_, Some(n)) if n <= i64::MAX as u64 => Integer::Long(n as i64),
+            _ => big(),
+        }
+    }
+
+    fn decimal_value(&mut self, digits: String, is_decimal: bool, suffix: Option<char>) -> Decimal {
+        match suffix {
+            Some('f') => {
+                let n: f32 = digits.parse().unwrap();
+                if n.is_infinite() {
+                    self.number_error("decimal literal is too large for `float`");
+                }
+                Decimal::Float(n)
+            }
+            Some('d') => {
+                let n: f64 = digits.parse().unwrap();
+                if n.is_infinite() {
+                    self.number_error("decimal literal is too large for `double`");
+                }
+                Decimal::Double(n)
+            }
+            Some('i' | 'l') => {
+                self.number_error("integer suffix on a decimal literal");
+                Decimal::BigDecimal(digits)
+            }
+            _ => Decimal::BigDecimal(digits),
+        }
+    }
+
+    fn number_error(&mut self, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic::error(
+            ErrorCode::InvalidNumber,
+            Span::new(self.start, self.pos),
+            message,
+        ));
+    }
+
+    /// Lexes a string whose opening delimiter has been consumed. A string
+    /// without interpolations becomes a single literal token holding its
+    /// decoded value; the raw text is available through the token's span. A
+    /// string containing `$` interpolations is instead queued as a
+    /// `GStringStart`, its parts and interpolations, and a `GStringEnd`, in
+    /// which case `None` is returned
+    fn lex_string(&mut self, kind: StringKind) -> Option<Token> {
+        let close = kind.closing_delimiter();
+        let string_start = self.start;
+
+        // stays empty unless the string turns out to be a gstring
+        let mut gstring = Vec::new();
+        let mut value = String::new();
+        let mut part_start = self.pos;
+        let end = loop {
+            let rest = &self.input[self.pos..];
+            if rest.starts_with(close) {
+                let end = self.pos;
+                self.pos += close.len();
+                break Some(end);
+            }
+
+            match self.peek_char() {
+                Some('$')
+                    if kind == StringKind::DollarSlashy && rest[1..].starts_with(['$', '/']) =>
+                {
+                    self.pos += 2;
+                    value.push_str(&rest[1..2]);
+                }
+                Some('$') if kind.interpolates() => {
+                    if !self.at_interpolation() {
+                        self.next_char();
+                        if kind.is_slashy() {
+                            value.push('$');
+                            continue;
+                        }
+                        self.diagnostics.push(
+                            Diagnostic::error(
+                                ErrorCode::InvalidEscape,
+                                Span::new(self.pos - 1, self.pos),
+                                "`$` must be followed by an identifier or `{`",
+                            )
+                            .with_suggestion(
+                                "escape the `$`",
+                                Span::new(self.pos - 1, self.pos),
+                                "\\$",
+                            ),
+                        );
+                        value.push('$');
+                        continue;
+                    }
+                    if gstring.is_empty() {
+                        gstring.push(SpannedToken {
+                            token: Token::GStringStart,
+                            span: Span::new(string_start, part_start),
+                        });
+                    }
+                    Self::push_gstring_part(&mut gstring, &mut value, part_start, self.pos);
+                    self.lex_interpolation(&mut gstring);
+                    part_start = self.pos;
+                }
+                Some('\\') if kind == StringKind::Slashy => {
+                    // `\/` is the only escape in a slashy string, and any
+                    // other backslash is kept as it is
+                    self.next_char();
+                    if self.peek_char() == Some('/') {
+                        self.next_char();
+                        value.push('/');
+                    } else {
+                        value.push('\\');
+                    }
+                }
+                Some('\\') if kind != StringKind::DollarSlashy => {
+                    self.next_char();
+                    self.lex_escape(&mut value);
+                }
+                Some('\n' | '\r') if !kind.is_multiline() => {
+                    self.start = string_start;
+                    self.unterminated_string(close);
+                    break None;
+                }
+                Some(c) => {
+                    self.next_char();
+                    value.push(c);
+                }
+                None => {
+                    self.start = string_start;
+                    self.unterminated_string(close);
+                    break None;
+                }
+            }
+        };
+
+        if gstring.is_empty() {
+            return Some(Token::Literal(Literal::String(value)));
+        }
+        let end = end.unwrap_or(self.pos);
+        Self::push_gstring_part(&mut gstring, &mut value, part_start, end);
+        gstring.push(SpannedToken {
+            token: Token::GStringEnd,
+            span: Span::new(end, self.pos),
+        });
+        self.pending.extend(gstring);
+        None
+    }
+
+    /// Whether the `$` at the current position begins an interpolation
+    fn at_interpolation(&self) -> bool {
+        let mut chars = self.input[self.pos..].chars().skip(1);
+        matches!(chars.next(), Some(c) if c == '{' || is_gstring_identifier_char(c, true))
+    }
+
+    /// Queues the literal text from `start` to `end` which has been decoded
+    /// into `value`, if there is any
+    fn push_gstring_part(
+        out: &mut Vec<SpannedToken>,
+        value: &mut String,
+        start: usize,
+        end: usize,
+    ) {
+        if start < end {
+            out.push(SpannedToken {
+                token: Token::GStringPart(std::mem::take(value)),
+                span: Span::new(start, end),
+            });
+        }
+    }
+
+    /// Lexes a `$path.to.value` or `${ expression }` within a gstring
+    fn lex_interpolation(&mut self, out: &mut Vec<SpannedToken>) {
+        let dollar = self.pos;
+        self.next_char();
+
+        if self.peek_char() != Some('{') {
+            out.push(SpannedToken {
+                token: Token::Dollar,
+                span: Span::new(dollar, self.pos),
+            });
+            loop {
+                let start = self.pos;
+                while let Some(c) = self.peek_char() {
+                    if !is_gstring_identifier_char(c, self.pos == start) {
+                        break;
+                    }
+                    self.next_char();
+                }
+                out.push(SpannedToken {
+                    token: identifier_or_keyword(&self.input[start..self.pos]),
+                    span: Span::new(start, self.pos),
+                });
+
+                // `.` only continues the path if another identifier follows
+                let mut rest = self.input[self.pos..].chars();
+                match (rest.next(), rest.next()) {
+                    (Some('.'), Some(c)) if is_gstring_identifier_char(c, true) => {
+                        out.push(SpannedToken {
+                            token: Token::Period,
+                            span: Span::new(self.pos, self.pos + 1),
+                        });
+                        self.pos += 1;
+                    }
+                    _ => return,
+                }
+            }
+        }
+
+        self.next_char();
+        out.push(SpannedToken {
+            token: Token::GStringInterpolationStart,
+            span: Span::new(dollar, self.pos),
+        });
+
+        // the expression is lexed like any other code, up to the `}` which
+        // balances the opening `${`
+        let mut inner = GroovyLexer::new(self.input);
+        inner.pos = self.pos;
+        let mut depth = 0;
+        while let Some(tok) = inner.lex() {
+            match tok.token {
+                // an interpolation holds a single expression, so line breaks
+                // within it never end anything
+                Token::NewLine => continue,
+                Token::CurlyBraceOpen | Token::GStringInterpolationStart => depth += 1,
+                Token::CurlyBraceClose if depth == 0 => {
+                    out.push(tok);
+                    break;
+                }
+                Token::CurlyBraceClose => depth -= 1,
+                _ => {}
+            }
+            out.push(tok);
+        }
+        self.pos = inner.pos;
+        self.diagnostics.append(&mut inner.diagnostics);
+    }
+
+    fn unterminated_string(&mut self, delimiter: &str) {
+        self.diagnostics.push(
+            Diagnostic::error(
+                ErrorCode::UnterminatedString,
+                Span::new(self.start, self.pos),
+                "unterminated string literal",
+            )
+            .with_suggestion(
+                format!("insert the missing `{}`", delimiter),
+                Span::empty(self.pos),
+                delimiter,
+            ),
+        );
+    }
+
+    /// Decodes the escape sequence following a `\` into `value`
+    fn lex_escape(&mut self, value: &mut String) {
+        let start = self.pos - 1;
+        let c = match self.peek_char() {
+            Some(c) => c,
+            // the enclosing string is unterminated, which is reported there
+            None => return,
+        };
+        self.next_char();
+
+        let decoded = match c {
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            's' => ' ',
+            't' => '\t',
+            '\\' | '\'' | '"' | '$' => c,
+            // a line continuation, which contributes nothing to the value
+            '\n' => return,
+            '\r' => {
+                if self.peek_char() == Some('\n') {
+                    self.next_char();
+                }
+                return;
+            }
+            'u' => match self.lex_unicode_escape(start) {
+                Some(c) => c,
+                None => return,
+            },
+            '0'..='7' => {
+                // up to three octal digits, with a maximum of `\377`
+                let max_len = if c <= '3' { 3 } else { 2 };
+                let mut code = c.to_digit(8).unwrap();
+                for _ in 1..max_len {
+                    match self.peek_char().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            self.next_char();
+                            code = code * 8 + digit;
+                        }
+                        None => break,
+                    }
+                }
+                char::from_u32(code).unwrap()
+            }
+            _ => {
+                self.diagnostics.push(Diagnostic::error(
+                    ErrorCode::InvalidEscape,
+                    Span::new(start, self.pos),
+                    format!("unknown escape sequence `\\{}`", c.escape_debug()),
+                ));
+                value.push('\\');
+                c
+            }
+        };
+        value.push(decoded);
+    }
+
+    /// Decodes the hex digits of a `\uXXXX` escape, whose `\u` has already
+    /// been consumed. The escapes encode UTF-16, so a surrogate pair written
+    /// as two escapes is combined into a single character
+    fn lex_unicode_escape(&mut self, start: usize) -> Option<char> {
+        let unit = self.lex_utf16_unit(start)?;
+        if !(0xD800..0xDC00).contains(&unit) {
+            return Some(char::from_u32(unit).unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+
+        let high = unit;
+        if self.input[self.pos..].starts_with("\\u") {
+            let low_start = self.pos;
+            self.pos += 2;
+            match self.lex_utf16_unit(low_start) {
+                Some(low @ 0xDC00..=0xDFFF) => {
+                    let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                    return char::from_u32(code);
+                }
+                _ => self.pos = low_start,
             }
+        }
+        Some(char::REPLACEMENT_CHARACTER)
+    }
+
+    fn lex_utf16_unit(&mut self, start: usize) -> Option<u32> {
+        // Java allows any number of `u`s, as in `\uuu0041`
+        while self.peek_char() == Some('u') {
+            self.next_char();
+        }
+        let digits = self.input[self.pos..]
+            .chars()
+            .take(4)
+            .take_while(char::is_ascii_hexdigit)
+            .count();
+        if digits < 4 {
+            self.pos += digits;
+            self.diagnostics.push(Diagnostic::error(
+                ErrorCode::InvalidEscape,
+                Span::new(start, self.pos),
+                "unicode escapes must have exactly four hex digits",
+            ));
+            return None;
+        }
+        let unit = u32::from_str_radix(&self.input[self.pos..self.pos + 4], 16).unwrap();
+        self.pos += 4;
+        Some(unit)
+    }
+
+    fn lex_exclamation(&mut self) -> Token {
+        if self.eat("==") {
+            Token::NotIdentical
+        } else if self.eat("=") {
+            Token::Ne
+        } else if self.eat_keyword("instanceof") {
+            Token::NotInstanceOf
+        } else if self.eat_keyword("in") {
+            Token::NotIn
         } else {
             Token::LogicalNot
         }
     }
 
-    fn lex_caret(&mut self) -> Token<'a> {
+    fn lex_caret(&mut self) -> Token {
         if self.peek_char() == Some('=') {
             self.next_char();
             Token::XorAssign
@@ -230,7 +910,7 @@ impl<'a> GroovyLexer<'a> {
         }
     }
 
-    fn lex_tilde(&mut self) -> Token<'a> {
+    fn lex_tilde(&mut self) -> Token {
         if self.peek_char() == Some('=') {
             self.next_char();
             Token::BitwiseNotAssign
@@ -239,7 +919,7 @@ impl<'a> GroovyLexer<'a> {
         }
     }
 
-    fn lex_equal(&mut self) -> Token<'a> {
+    fn lex_equal(&mut self) -> Token {
         match self.peek_char() {
             Some('=') => {
                 self.next_char();
@@ -263,7 +943,14 @@ impl<'a> GroovyLexer<'a> {
         }
     }
 
-    fn lex_less_than(&mut self) -> Token<'a> {
+    fn lex_less_than(&mut self) -> Token {
+        if self.eat("=>") {
+            return Token::Spaceship;
+        } else if self.eat("..<") {
+            return Token::ExclusiveBothRange;
+        } else if self.eat("..") {
+            return Token::ExclusiveLeftRange;
+        }
         match self.peek_char() {
             Some('<') => {
                 self.next_char();
@@ -278,16 +965,23 @@ impl<'a> GroovyLexer<'a> {
                 self.next_char();
                 Token::Le
             }
+            Some('>') => {
+                self.next_char();
+                Token::Diamond
+            }
             _ => Token::Lt,
         }
     }
 
-    fn lex_greater_than(&mut self) -> Token<'a> {
+    fn lex_greater_than(&mut self) -> Token {
         match self.peek_char() {
             Some('>') => {
                 self.next_char();
-                if self.peek_char() == Some('=') {
-                    self.next_char();
+                if self.eat(">=") {
+                    Token::UnsignedShrAssign
+                } else if self.eat(">") {
+                    Token::UnsignedShr
+                } else if self.eat("=") {
                     Token::ShrAssign
                 } else {
                     Token::Shr
@@ -301,16 +995,53 @@ impl<'a> GroovyLexer<'a> {
         }
     }
 
-    fn whitespace(&mut self) {
+    /// Skips whitespace, stopping after the first line break if it is
+    /// significant and returning a `NewLine` token for it
+    fn whitespace(&mut self) -> Option<SpannedToken> {
         while let Some(c) = self.peek_char() {
-            if c.is_ascii_whitespace() {
-                self.next_char();
-            } else {
-                self.start = self.pos;
-                return;
+            if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.next_char();
+            if c == '\n' && self.newline_significant() {
+                return Some(SpannedToken {
+                    token: Token::NewLine,
+                    span: Span::new(self.pos - 1, self.pos),
+                });
             }
         }
         self.start = self.pos;
+        None
+    }
+
+    /// Line breaks only end statements directly inside a block, never within
+    /// `()` or `[]`
+    fn newline_significant(&self) -> bool {
+        self.newline_allowed
+            && !matches!(
+                self.open_brackets.last(),
+                Some(Token::ParenOpen | Token::SquareBraceOpen | Token::SafeIndex)
+            )
+    }
+
+    /// Consumes `expected` if the input continues with it
+    fn eat(&mut self, expected: &str) -> bool {
+        if self.input[self.pos..].starts_with(expected) {
+            self.pos += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Like [`Self::eat`], but only if `keyword` is not the start of a longer
+    /// identifier, so that `!inside` stays a negation
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let rest = &self.input[self.pos..];
+        let boundary = rest
+            .strip_prefix(keyword)
+            .map(|after| !after.chars().next().is_some_and(is_identifier_part));
+        boundary == Some(true) && self.eat(keyword)
     }
 
     fn next_char(&mut self) -> Option<char> {
@@ -333,10 +1064,637 @@ impl<'a> GroovyLexer<'a> {
     }
 }
 
+fn is_identifier_part(c: char) -> bool {
+    matches!(c,
+        'a'..='z'
+        | 'A'..='Z'
+        | '\u{00C0}'..='\u{00D6}'
+        | '\u{00D8}'..='\u{00F6}'
+        | '\u{00F8}'..='\u{00FF}'
+        | '\u{0100}'..='\u{FFFE}'
+        | '_'
+        | '$'
+        | '0'..='9'
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringKind {
+    /// '...'
+    Single,
+    /// "..."
+    Double,
+    /// '''...'''
+    TripleSingle,
+    /// """..."""
+    TripleDouble,
+    /// /.../
+    Slashy,
+    /// $/.../$
+    DollarSlashy,
+}
+
+impl StringKind {
+    fn closing_delimiter(self) -> &'static str {
+        match self {
+            StringKind::Single => "'",
+            StringKind::Double => "\"",
+            StringKind::TripleSingle => "'''",
+            StringKind::TripleDouble => "\"\"\"",
+            StringKind::Slashy => "/",
+            StringKind::DollarSlashy => "/$",
+        }
+    }
+
+    fn interpolates(self) -> bool {
+        !matches!(self, StringKind::Single | StringKind::TripleSingle)
+    }
+
+    fn is_multiline(self) -> bool {
+        !matches!(self, StringKind::Single | StringKind::Double)
+    }
+
+    fn is_slashy(self) -> bool {
+        matches!(self, StringKind::Slashy | StringKind::DollarSlashy)
+    }
+}
+
+/// The text of a Groovydoc comment without its delimiters or the `*`s which
+/// begin each line
+fn doc_comment_text(raw: &str) -> String {
+    let lines: Vec<&str> = raw
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn underscores_between_digits(raw: &str, radix: u32) -> bool {
+    let chars: Vec<char> = raw.chars().collect();
+    chars.iter().enumerate().all(|(i, &c)| {
+        if c != '_' {
+            return true;
+        }
+        let before = chars[..i].iter().rev().find(|&&c| c != '_');
+        let after = chars[i + 1..].iter().find(|&&c| c != '_');
+        matches!(before, Some(c) if c.is_digit(radix))
+            && matches!(after, Some(c) if c.is_digit(radix))
+    })
+}
+
+/// Converts digits in the given radix to decimal, however many there are
+fn to_decimal_string(digits: &str, radix: u32) -> String {
+    // little-endian limbs, each holding nine decimal digits
+    const LIMB: u64 = 1_000_000_000;
+    let mut limbs: Vec<u64> = vec![0];
+    for c in digits.chars() {
+        let mut carry = c.to_digit(radix).unwrap() as u64;
+        for limb in &mut limbs {
+            let n = *limb * radix as u64 + carry;
+            *limb = n % LIMB;
+            carry = n / LIMB;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+
+    let mut out = limbs.last().unwrap().to_string();
+    for limb in limbs.iter().rev().skip(1) {
+        out.push_str(&format!("{:09}", limb));
+    }
+    out
+}
+
+/// Whether `token` can end an operand, in which case a `/` following it is a
+/// division rather than the start of a slashy string
+fn ends_operand(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Identifier(..)
+            | Token::Literal(..)
+            | Token::Keyword(Keywords::This | Keywords::Super)
+            | Token::ParenClose
+            | Token::SquareBraceClose
+            | Token::CurlyBraceClose
+            | Token::GStringEnd
+            | Token::PlusPlus
+            | Token::MinusMinus
+    )
+}
+
+/// Whether a statement must continue after `token`, as it is an operator or
+/// separator which cannot end one
+fn continues_statement(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::ExprEnd
+            | Token::NewLine
+            | Token::CurlyBraceOpen
+            | Token::Period
+            | Token::SingleEqual
+            | Token::LogicalNot
+            | Token::Tilde
+            | Token::Add
+            | Token::AddAssign
+            | Token::Sub
+            | Token::SubAssign
+            | Token::Mul
+            | Token::MulAssign
+            | Token::Div
+            | Token::DivAssign
+            | Token::Rem
+            | Token::RemAssign
+            | Token::Pow
+            | Token::PowAssign
+            | Token::Eq
+            | Token::Ne
+            | Token::Lt
+            | Token::Gt
+            | Token::Le
+            | Token::Ge
+            | Token::Identical
+            | Token::NotIdentical
+            | Token::LogicalAnd
+            | Token::LogicalOr
+            | Token::BitwiseAnd
+            | Token::BitwiseOr
+            | Token::BitwiseAndAssign
+            | Token::BitwiseOrAssign
+            | Token::Xor
+            | Token::XorAssign
+            | Token::Shl
+            | Token::Shr
+            | Token::ShlAssign
+            | Token::ShrAssign
+            | Token::Find
+            | Token::Match
+            | Token::BitwiseNotAssign
+            | Token::Comma
+            | Token::UnsignedShr
+            | Token::UnsignedShrAssign
+            | Token::QuestionMark
+            | Token::Colon
+            | Token::Elvis
+            | Token::ElvisAssignment
+            | Token::SafeNavigation
+            | Token::DirectFieldAccess
+            | Token::MethodPointer
+            | Token::MethodReference
+            | Token::SpreadDot
+            | Token::SpreadMap
+            | Token::InclusiveRange
+            | Token::ExclusiveRange
+            | Token::ExclusiveLeftRange
+            | Token::ExclusiveBothRange
+            | Token::Arrow
+            | Token::Spaceship
+            | Token::NotIn
+            | Token::NotInstanceOf
+            | Token::Keyword(Keywords::In | Keywords::InstanceOf)
+    )
+}
+
+fn identifier_or_keyword(text: &str) -> Token {
+    match Keywords::try_from(Identifier::new(text)) {
+        Ok(Keywords::True) => Token::Literal(Literal::True),
+        Ok(Keywords::False) => Token::Literal(Literal::False),
+        Ok(Keywords::Null) => Token::Literal(Literal::Null),
+        Ok(keyword) if keyword.is_contextual() => Token::Identifier(Identifier::new(text)),
+        Ok(keyword) => Token::Keyword(keyword),
+        Err(ident) => Token::Identifier(ident),
+    }
+}
+
+/// Whether `c` can appear in an identifier interpolated into a gstring. These
+/// follow the same rules as other identifiers, except that they cannot
+/// contain `$`
+fn is_gstring_identifier_char(c: char, first: bool) -> bool {
+    match c {
+        'a'..='z'
+        | 'A'..='Z'
+        | '\u{00C0}'..='\u{00D6}'
+        | '\u{00D8}'..='\u{00F6}'
+        | '\u{00F8}'..='\u{00FF}'
+        | '\u{0100}'..='\u{FFFE}'
+        | '_' => true,
+        '0'..='9' => !first,
+        _ => false,
+    }
+}
+
 impl<'a> Iterator for GroovyLexer<'a> {
-    type Item = Token<'a>;
+    type Item = SpannedToken;
     fn next(&mut self) -> Option<Self::Item> {
-        self.start = self.pos;
-        Some(self.lex())
+        self.lex()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_spans() {
+        let input = "class  Foo {\n\tint x = 10; }";
+        let spans: Vec<(Span, &str)> = GroovyLexer::new(input)
+            .map(|tok| (tok.span, &input[tok.span.start..tok.span.end]))
+            .collect();
+        assert_eq!(
+            spans.iter().map(|(_, s)| *s).collect::<Vec<_>>(),
+            vec!["class", "Foo", "{", "int", "x", "=", "10", ";", "}"]
+        );
+        assert_eq!(spans[1].0, Span::new(7, 10));
+        assert_eq!(spans[3].0, Span::new(14, 17));
+    }
+
+    #[test]
+    fn comment_is_not_part_of_span() {
+        let input = "a // comment\n  b";
+        let spans: Vec<Span> = GroovyLexer::new(input).map(|tok| tok.span).collect();
+        assert_eq!(
+            spans,
+            [Span::new(0, 1), Span::new(12, 13), Span::new(15, 16)]
+        );
+    }
+
+    fn strings(input: &str) -> (Vec<String>, Vec<Diagnostic>) {
+        let lexed = GroovyLexer::tokenize(input);
+        let values = lexed
+            .tokens
+            .into_iter()
+            .filter_map(|tok| match tok.token {
+                Token::Literal(Literal::String(s)) => Some(s),
+                _ => None,
+            })
+            .collect();
+        (values, lexed.diagnostics)
+    }
+
+    #[test]
+    fn quoted_strings() {
+        let (values, diagnostics) =
+            strings(r#"'a' "b" '' "it's" 'say "hi"' '''x'y''z''' """ "quoted" """"#);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(
+            values,
+            vec!["a", "b", "", "it's", "say \"hi\"", "x'y''z", " \"quoted\" "]
+        );
+
+        let input = "x = \"\"\"one\n  two\"\"\" + y";
+        let lexed = GroovyLexer::tokenize(input);
+        let string = &lexed.tokens[2];
+        assert_eq!(
+            string.token,
+            Token::Literal(Literal::String("one\n  two".to_owned()))
+        );
+        assert_eq!(
+            &input[string.span.start..string.span.end],
+            "\"\"\"one\n  two\"\"\""
+        );
+    }
+
+    #[test]
+    fn escape_sequences() {
+        let (values, diagnostics) = strings(
+            r#"'\b\f\n\r\s\t\\\'\"\$' '\u0041\uuu00e9' '\uD834\uDD1E' '\0\101\477' '''a\
+b'''"#,
+        );
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(
+            values,
+            vec!["\u{8}\u{c}\n\r \t\\'\"$", "Aé", "𝄞", "\0A'7", "ab"]
+        );
+    }
+
+    #[test]
+    fn malformed_strings() {
+        let (values, diagnostics) = strings("'\\q' '\\u12x' 'open\n\"\"\"never closed");
+        assert_eq!(values, vec!["\\q", "x", "open", "never closed"]);
+        let codes: Vec<_> = diagnostics.iter().map(|diag| diag.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                ErrorCode::InvalidEscape,
+                ErrorCode::InvalidEscape,
+                ErrorCode::UnterminatedString,
+                ErrorCode::UnterminatedString,
+            ]
+        );
+        assert_eq!(diagnostics[2].span, Span::new(13, 18));
+        assert_eq!(diagnostics[3].suggestions[0].replacement, "\"\"\"");
+    }
+
+    #[test]
+    fn gstring_interpolations() {
+        let input = r#""a $b.c. ${ d + "x${e}" } \$f""#;
+        let tokens: Vec<Token> = GroovyLexer::new(input).map(|tok| tok.token).collect();
+        let ident = |s: &str| Token::Identifier(Identifier::new(s));
+        let part = |s: &str| Token::GStringPart(s.to_owned());
+        assert_eq!(
+            tokens,
+            vec![
+                Token::GStringStart,
+                part("a "),
+                Token::Dollar,
+                ident("b"),
+                Token::Period,
+                ident("c"),
+                part(". "),
+                Token::GStringInterpolationStart,
+                ident("d"),
+                Token::Add,
+                Token::GStringStart,
+                part("x"),
+                Token::GStringInterpolationStart,
+                ident("e"),
+                Token::CurlyBraceClose,
+                Token::GStringEnd,
+                Token::CurlyBraceClose,
+                part(" $f"),
+                Token::GStringEnd,
+            ]
+        );
+
+        // the parts of the outer string cover all of its text
+        let spans: Vec<&str> = GroovyLexer::new(r#""a $b ${c}""#)
+            .map(|tok| &r#""a $b ${c}""#[tok.span.start..tok.span.end])
+            .collect();
+        assert_eq!(spans, vec!["\"", "a ", "$", "b", " ", "${", "c", "}", "\""]);
+    }
+
+    #[test]
+    fn dollar_without_interpolation() {
+        let (values, diagnostics) = strings(r#""cost: $5" 'cost: $5'"#);
+        assert_eq!(values, vec!["cost: $5", "cost: $5"]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span, Span::new(7, 8));
+        assert_eq!(diagnostics[0].suggestions[0].replacement, "\\$");
+    }
+
+    #[test]
+    fn slashy_strings() {
+        let (values, diagnostics) = strings(r#"a =~ /\d+\/\w*$/; b = $/C:\dir\$$x$/end/$"#);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(values, vec![r"\d+/\w*$", r"C:\dir\$x/end"]);
+
+        let input = "/a$b/";
+        let tokens: Vec<Token> = GroovyLexer::new(input).map(|tok| tok.token).collect();
+        assert_eq!(tokens[0], Token::GStringStart);
+        assert_eq!(tokens[3], Token::Identifier(Identifier::new("b")));
+        assert_eq!(tokens.last(), Some(&Token::GStringEnd));
+    }
+
+    #[test]
+    fn slash_after_an_operand_is_division() {
+        let input = "x / 2 / y; f(a) /2/ b[1] / 3; /re/ + (/re/)";
+        let tokens: Vec<Token> = GroovyLexer::new(input).map(|tok| tok.token).collect();
+        let divisions = tokens.iter().filter(|tok| **tok == Token::Div).count();
+        let regexes = tokens
+            .iter()
+            .filter(|tok| **tok == Token::Literal(Literal::String("re".to_owned())))
+            .count();
+        assert_eq!(divisions, 5);
+        assert_eq!(regexes, 2);
+    }
+
+    fn numbers(input: &str) -> (Vec<Literal>, Vec<Diagnostic>) {
+        let lexed = GroovyLexer::tokenize(input);
+        let values = lexed
+            .tokens
+            .into_iter()
+            .filter_map(|tok| match tok.token {
+                Token::Literal(lit @ (Literal::Integer(..) | Literal::Decimal(..))) => Some(lit),
+                _ => None,
+            })
+            .collect();
+        (values, lexed.diagnostics)
+    }
+
+    #[test]
+    fn numeric_literals() {
+        let (values, diagnostics) = numbers(
+            "0xFF 0b1010 017 1_000_000 3.14 1e10 10L 2.5f 1G 1.5G 7i 2d 0x7fff_ffffL \
+             2147483648 99999999999999999999 0xFFFFFFFFFFFFFFFFFFFF 1.5e-3d 0",
+        );
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        let int = |n| Literal::Integer(Integer::Int(n));
+        let big_int = |n: &str| Literal::Integer(Integer::BigInteger(n.to_owned()));
+        let big_dec = |n: &str| Literal::Decimal(Decimal::BigDecimal(n.to_owned()));
+        assert_eq!(
+            values,
+            vec![
+                int(255),
+                int(10),
+                int(15),
+                int(1_000_000),
+                big_dec("3.14"),
+                big_dec("1e10"),
+                Literal::Integer(Integer::Long(10)),
+                Literal::Decimal(Decimal::Float(2.5)),
+                big_int("1"),
+                big_dec("1.5"),
+                int(7),
+                Literal::Decimal(Decimal::Double(2.0)),
+                Literal::Integer(Integer::Long(0x7fff_ffff)),
+                Literal::Integer(Integer::Long(2_147_483_648)),
+                big_int("99999999999999999999"),
+                big_int("1208925819614629174706175"),
+                Literal::Decimal(Decimal::Double(1.5e-3)),
+                int(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_are_not_confused_with_member_access_or_ranges() {
+        let tokens: Vec<Token> = GroovyLexer::new("1.abs() 1..2")
+            .map(|tok| tok.token)
+            .collect();
+        assert_eq!(tokens[0], Token::Literal(Literal::Integer(Integer::Int(1))));
+        assert_eq!(tokens[1], Token::Period);
+        assert_eq!(tokens[5], Token::Literal(Literal::Integer(Integer::Int(1))));
+    }
+
+    #[test]
+    fn significant_newlines() {
+        let input =
+            "a = 1 +\n  2 // two\n\n\nf(1,\n  2)\nb = [\n  1]\nc.\n  d\n{\n  \"${\n x}\"\n}";
+        let tokens: Vec<&str> = GroovyLexer::new(input)
+            .map(|tok| match tok.token {
+                Token::NewLine => "\\n",
+                _ => &input[tok.span.start..tok.span.end],
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                "a", "=", "1", "+", "2", "\\n", "f", "(", "1", ",", "2", ")", "\\n", "b", "=", "[",
+                "1", "]", "\\n", "c", ".", "d", "\\n", "{", "\"", "${", "x", "}", "\"", "\\n", "}"
+            ]
+        );
+    }
+
+    #[test]
+    fn operators_use_maximal_munch() {
+        let input = "?. ?: ?= ?[ ? .@ .& :: : *. *: ... ..< .. <..< <.. <=> <> <= << \
+                     >>>= >>> >>= >> @ !in !instanceof != !== ++ -- ->";
+        let tokens: Vec<&str> = GroovyLexer::new(input)
+            .map(|tok| &input[tok.span.start..tok.span.end])
+            .collect();
+        assert_eq!(tokens, input.split_whitespace().collect::<Vec<_>>());
+
+        // `!in` is only an operator when it is not the start of an identifier
+        let input = "!inside a.b";
+        let tokens: Vec<&str> = GroovyLexer::new(input)
+            .map(|tok| &input[tok.span.start..tok.span.end])
+            .collect();
+        assert_eq!(tokens, ["!", "inside", "a", ".", "b"]);
+
+        let tokens: Vec<Token> = GroovyLexer::new("1..<2 x<..y !in")
+            .map(|tok| tok.token)
+            .collect();
+        assert_eq!(tokens[1], Token::ExclusiveRange);
+        assert_eq!(tokens[4], Token::ExclusiveLeftRange);
+        assert_eq!(tokens[6], Token::NotIn);
+    }
+
+    #[test]
+    fn adjacent_operators_use_maximal_munch() {
+        let lex =
+            |input: &str| -> Vec<Token> { GroovyLexer::new(input).map(|tok| tok.token).collect() };
+        let ident = |name| Token::Identifier(Identifier::new(name));
+
+        // shifts, and assignments built on them
+        assert_eq!(
+            lex("a>>>=b>>>c>>=d"),
+            [
+                ident("a"),
+                Token::UnsignedShrAssign,
+                ident("b"),
+                Token::UnsignedShr,
+                ident("c"),
+                Token::ShrAssign,
+                ident("d"),
+            ]
+        );
+        // safe navigation, spread and elvis
+        assert_eq!(
+            lex("a?.b*.c?:d?=e"),
+            [
+                ident("a"),
+                Token::SafeNavigation,
+                ident("b"),
+                Token::SpreadDot,
+                ident("c"),
+                Token::Elvis,
+                ident("d"),
+                Token::ElvisAssignment,
+                ident("e"),
+            ]
+        );
+        // comparisons and regex operators
+        assert_eq!(
+            lex("a<=>b=~c==~d===e"),
+            [
+                ident("a"),
+                Token::Spaceship,
+                ident("b"),
+                Token::Find,
+                ident("c"),
+                Token::Match,
+                ident("d"),
+                Token::Identical,
+                ident("e"),
+            ]
+        );
+        // powers
+        assert_eq!(
+            lex("a**=b**c*d"),
+            [
+                ident("a"),
+                Token::PowAssign,
+                ident("b"),
+                Token::Pow,
+                ident("c"),
+                Token::Mul,
+                ident("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_character_between_operators() {
+        let lexed = GroovyLexer::tokenize("a>>>#=b");
+        let tokens: Vec<Token> = lexed.tokens.into_iter().map(|tok| tok.token).collect();
+        // the unknown character does not join or split its neighbours
+        assert_eq!(
+            tokens,
+            [
+                Token::Identifier(Identifier::new("a")),
+                Token::UnsignedShr,
+                Token::SingleEqual,
+                Token::Identifier(Identifier::new("b")),
+            ]
+        );
+        assert_eq!(lexed.diagnostics.len(), 1);
+        assert_eq!(lexed.diagnostics[0].code, ErrorCode::UnknownCharacter);
+        assert_eq!(lexed.diagnostics[0].span, Span::new(4, 5));
+    }
+
+    #[test]
+    fn malformed_numbers() {
+        let (_, diagnostics) =
+            numbers("2147483648i 9223372036854775808L 1e39f 1e309d 1_ 0x 09 12abc 1.5L");
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "integer literal is too large for `int`",
+                "integer literal is too large for `long`",
+                "decimal literal is too large for `float`",
+                "decimal literal is too large for `double`",
+                "`_` may only appear between digits",
+                "hex literal has no digits",
+                "invalid digit `9` in octal literal",
+                "invalid suffix `abc` on numeric literal",
+                "integer suffix on a decimal literal",
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code == ErrorCode::InvalidNumber));
+        assert_eq!(diagnostics[0].span, Span::new(0, 11));
+    }
+
+    #[test]
+    fn comments_and_shebang() {
+        let input = "#!/usr/bin/env groovy\n/* block\n */ a /**/ b /**\n * Adds things.\n *\n * @param x\n */ c /* open";
+        let lexed = GroovyLexer::tokenize(input);
+        let tokens: Vec<&str> = lexed
+            .tokens
+            .iter()
+            .map(|tok| &input[tok.span.start..tok.span.end])
+            .collect();
+        assert_eq!(tokens, vec!["a", "b", "c"]);
+
+        assert_eq!(lexed.doc_comments.len(), 1);
+        assert_eq!(lexed.doc_comments[0].node, "Adds things.\n\n@param x");
+        assert_eq!(&input[lexed.doc_comments[0].span.end..], " c /* open");
+
+        assert_eq!(lexed.diagnostics.len(), 1);
+        assert_eq!(lexed.diagnostics[0].message, "unterminated block comment");
+
+        // a shebang anywhere else is an error
+        assert_eq!(GroovyLexer::tokenize("a\n#!b").diagnostics.len(), 1);
     }
 }
@@ -0,0 +1,111 @@
+//! Conversions between our own representations and those of `lsp_types`
+
+use lsp_types::{DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Url};
+use serde_json::json;
+
+use crate::{
+    diagnostic::{Diagnostic, Severity},
+    span::{LineCol, LineIndex, Span},
+};
+
+pub fn position(index: &LineIndex, offset: usize) -> lsp_types::Position {
+    let LineCol { line, col } = index.line_col(offset);
+    lsp_types::Position::new(line, col)
+}
+
+pub fn range(index: &LineIndex, span: Span) -> lsp_types::Range {
+    lsp_types::Range::new(position(index, span.start), position(index, span.end))
+}
+
+/// The byte offset of `position`, or `None` if it lies past the last line
+pub fn offset(index: &LineIndex, position: lsp_types::Position) -> Option<usize> {
+    index.offset(LineCol {
+        line: position.line,
+        col: position.character,
+    })
+}
+
+pub fn severity(severity: Severity) -> DiagnosticSeverity {
+    match severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Information => DiagnosticSeverity::INFORMATION,
+        Severity::Hint => DiagnosticSeverity::HINT,
+    }
+}
+
+/// Labels become related information pointing back into the same file, and
+/// suggestions are carried in `data` so that code actions can apply them
+/// later without recomputing the diagnostic
+pub fn diagnostic(uri: &Url, index: &LineIndex, diag: &Diagnostic) -> lsp_types::Diagnostic {
+    let related_information = if diag.labels.is_empty() {
+        None
+    } else {
+        Some(
+            diag.labels
+                .iter()
+                .map(|label| DiagnosticRelatedInformation {
+                    location: Location::new(uri.clone(), range(index, label.span)),
+                    message: label.message.clone(),
+                })
+                .collect(),
+        )
+    };
+
+    let data = if diag.suggestions.is_empty() {
+        None
+    } else {
+        Some(json!(diag
+            .suggestions
+            .iter()
+            .map(|suggestion| json!({
+                "title": suggestion.message,
+                "range": range(index, suggestion.span),
+                "newText": suggestion.replacement,
+            }))
+            .collect::<Vec<_>>()))
+    };
+
+    lsp_types::Diagnostic {
+        range: range(index, diag.span),
+        severity: Some(severity(diag.severity)),
+        code: Some(NumberOrString::String(diag.code.as_str().to_owned())),
+        code_description: None,
+        source: Some(env!("CARGO_PKG_NAME").to_owned()),
+        message: diag.message.clone(),
+        related_information,
+        tags: None,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::ErrorCode;
+
+    #[test]
+    fn converts_labels_and_suggestions() {
+        let text = "class A {\n  void f() {";
+        let index = LineIndex::new(text);
+        let uri = Url::parse("file:///a.groovy").unwrap();
+        let diag = Diagnostic::error(
+            ErrorCode::UnclosedDelimiter,
+            Span::empty(text.len()),
+            "expected `}`, found end of file",
+        )
+        .with_label(Span::new(8, 9), "unclosed delimiter")
+        .with_suggestion("insert the missing `}`", Span::empty(text.len()), "}");
+
+        let lsp = diagnostic(&uri, &index, &diag);
+        assert_eq!(lsp.range.start, lsp_types::Position::new(1, 12));
+        assert_eq!(lsp.severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(lsp.code, Some(NumberOrString::String("E0003".to_owned())));
+
+        let related = &lsp.related_information.unwrap()[0];
+        assert_eq!(related.location.range.start, lsp_types::Position::new(0, 8));
+        assert_eq!(related.message, "unclosed delimiter");
+
+        assert_eq!(lsp.data.unwrap()[0]["newText"], "}");
+    }
+}
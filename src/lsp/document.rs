@@ -0,0 +1,130 @@
+//! The text of the files the client currently has open
+
+use lsp_types::TextDocumentContentChangeEvent;
+
+use crate::{
+    lsp::convert,
+    span::{LineIndex, Span},
+};
+
+#[derive(Debug)]
+pub struct Document {
+    text: String,
+    /// The version number assigned by the client, which increases with every
+    /// change
+    version: i32,
+    line_index: LineIndex,
+}
+
+impl Document {
+    pub fn new(text: String, version: i32) -> Self {
+        Self {
+            line_index: LineIndex::new(&text),
+            text,
+            version,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
+    pub fn set_version(&mut self, version: i32) {
+        self.version = version;
+    }
+
+    /// Applies a single edit from the client. An edit without a range
+    /// replaces the entire document. Positions are resolved against the text
+    /// as it stands after all previous edits, so a batch of changes must be
+    /// applied in order. The line index is updated in place, rescanning only
+    /// the lines the edit touched
+    pub fn apply_change(&mut self, change: TextDocumentContentChangeEvent) {
+        let range = match change.range {
+            Some(range) => range,
+            None => {
+                self.line_index = LineIndex::new(&change.text);
+                self.text = change.text;
+                return;
+            }
+        };
+
+        let len = self.text.len();
+        let start = convert::offset(&self.line_index, range.start).unwrap_or(len);
+        let end = convert::offset(&self.line_index, range.end).unwrap_or(len);
+        let (start, end) = if start <= end {
+            (start, end)
+        } else {
+            (end, start)
+        };
+
+        self.text.replace_range(start..end, &change.text);
+        self.line_index
+            .apply_edit(&self.text, Span::new(start, end), change.text.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Position, Range};
+
+    fn edit(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(
+                Position::new(start.0, start.1),
+                Position::new(end.0, end.1),
+            )),
+            range_length: None,
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn applies_edits_in_order() {
+        let mut doc = Document::new("class A {\n}\n".to_owned(), 1);
+        doc.apply_change(edit((1, 0), (1, 0), "  void f(int a) {}\n"));
+        doc.apply_change(edit((0, 6), (0, 7), "Foo"));
+        doc.apply_change(edit((1, 17), (1, 17), "\n    return a\n  "));
+        assert_eq!(
+            doc.text(),
+            "class Foo {\n  void f(int a) {\n    return a\n  }\n}\n"
+        );
+        assert_eq!(doc.line_index().line_count(), 6);
+        assert_eq!(doc.line_index(), &LineIndex::new(doc.text()));
+    }
+
+    #[test]
+    fn positions_are_utf16() {
+        let mut doc = Document::new("def s = '𝄞é'\n".to_owned(), 1);
+        // replace `é`, which follows a surrogate pair
+        doc.apply_change(edit((0, 11), (0, 12), "e"));
+        assert_eq!(doc.text(), "def s = '𝄞e'\n");
+        doc.apply_change(edit((0, 9), (0, 11), ""));
+        assert_eq!(doc.text(), "def s = 'e'\n");
+    }
+
+    #[test]
+    fn out_of_range_edits_are_clamped() {
+        let mut doc = Document::new("a\nb".to_owned(), 1);
+        doc.apply_change(edit((1, 40), (7, 0), "c"));
+        assert_eq!(doc.text(), "a\nbc");
+        let mut doc = Document::new("a\r\nb".to_owned(), 1);
+        doc.apply_change(edit((0, 40), (0, 40), "c"));
+        assert_eq!(doc.text(), "ac\r\nb");
+        assert_eq!(doc.line_index(), &LineIndex::new(doc.text()));
+        doc.apply_change(TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "new".to_owned(),
+        });
+        assert_eq!(doc.text(), "new");
+    }
+}
@@ -0,0 +1,638 @@
+//! The language server itself: the lifecycle state machine and the dispatch
+//! of incoming requests and notifications to their handlers
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    rc::Rc,
+};
+
+use lsp_types::{
+    notification::{self, Notification as LspNotification},
+    request::{self, Request as LspRequest},
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    InitializeParams, InitializeResult, LogMessageParams, MessageType, PublishDiagnosticsParams,
+    ServerCapabilities, ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, Url,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{
+    db::{AnalysisDatabase, FileId, RootDatabase, SourceDatabase},
+    diagnostic::Diagnostic,
+};
+
+use document::Document;
+use transport::{ErrorCode, Message, Notification, Request, Response, ResponseError};
+
+mod convert;
+mod document;
+mod transport;
+
+/// Runs the server over stdin and stdout, returning the process exit code
+pub fn run_stdio() -> i32 {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut server = Server::new(stdout.lock());
+    // an I/O error means the connection itself is gone, so there is no
+    // client left to tell
+    server.run(&mut stdin.lock()).unwrap_or(1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Waiting for the client to send `initialize`
+    Uninitialized,
+    Running,
+    /// `shutdown` has been received, and we are waiting for `exit`
+    ShuttingDown,
+}
+
+pub struct Server<W: Write> {
+    writer: W,
+    state: State,
+    documents: HashMap<Url, Document>,
+    db: RootDatabase,
+    /// The id given to each document the first time it was opened
+    file_ids: HashMap<Url, FileId>,
+    /// The diagnostics last sent for each open document, so that only those
+    /// which have changed are sent again
+    published: HashMap<Url, Rc<Vec<Diagnostic>>>,
+}
+
+type HandlerResult<T> = Result<T, ResponseError>;
+
+impl<W: Write> Server<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            state: State::Uninitialized,
+            documents: HashMap::new(),
+            db: RootDatabase::new(),
+            file_ids: HashMap::new(),
+            published: HashMap::new(),
+        }
+    }
+
+    /// Processes messages until the client sends `exit` or closes the input,
+    /// returning the exit code the process should use
+    pub fn run(&mut self, reader: &mut impl BufRead) -> io::Result<i32> {
+        loop {
+            let msg = match transport::read_message(reader) {
+                Ok(Some(msg)) => msg,
+                Ok(None) => break,
+                // the connection is still usable after a message which could
+                // not be decoded, and as its id is unknown, so is the reply's
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    let error = ResponseError::new(ErrorCode::ParseError, e.to_string());
+                    self.send(Message::Response(Response::err(None, error)))?;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            match msg {
+                Message::Request(req) => self.handle_request(req)?,
+                Message::Notification(not) if not.method == notification::Exit::METHOD => {
+                    return Ok(if self.state == State::ShuttingDown {
+                        0
+                    } else {
+                        1
+                    });
+                }
+                Message::Notification(not) => self.handle_notification(not)?,
+                // we never send requests to the client, so there is nothing
+                // waiting on a response
+                Message::Response(..) => {}
+            }
+        }
+        Ok(1)
+    }
+
+    fn send(&mut self, msg: Message) -> io::Result<()> {
+        transport::write_message(&mut self.writer, &msg)
+    }
+
+    pub fn send_notification<N: LspNotification>(&mut self, params: N::Params) -> io::Result<()> {
+        self.send(Message::Notification(Notification::new(N::METHOD, params)))
+    }
+
+    /// Writes to the client's log, as a notification can't be answered with
+    /// an error
+    fn log_message(&mut self, typ: MessageType, message: String) -> io::Result<()> {
+        self.send_notification::<notification::LogMessage>(LogMessageParams { typ, message })
+    }
+
+    fn handle_request(&mut self, req: Request) -> io::Result<()> {
+        let Request { id, method, params } = req;
+
+        let result = match (self.state, method.as_str()) {
+            (State::Uninitialized, request::Initialize::METHOD) => {
+                self.dispatch::<request::Initialize>(params, Self::initialize)
+            }
+            (State::Uninitialized, _) => Err(ResponseError::new(
+                ErrorCode::ServerNotInitialized,
+                "the server has not been initialized",
+            )),
+            (State::ShuttingDown, _) => Err(ResponseError::new(
+                ErrorCode::InvalidRequest,
+                "the server is shutting down",
+            )),
+            (State::Running, request::Initialize::METHOD) => Err(ResponseError::new(
+                ErrorCode::InvalidRequest,
+                "the server has already been initialized",
+            )),
+            (State::Running, request::Shutdown::METHOD) => {
+                self.dispatch::<request::Shutdown>(params, Self::shutdown)
+            }
+            (State::Running, _) => Err(ResponseError::new(
+                ErrorCode::MethodNotFound,
+                format!("unhandled method: {}", method),
+            )),
+        };
+
+        let response = match result {
+            Ok(value) => Response::ok(id, value),
+            Err(error) => Response::err(Some(id), error),
+        };
+        self.send(Message::Response(response))
+    }
+
+    fn handle_notification(&mut self, not: Notification) -> io::Result<()> {
+        // notifications sent before initialization are dropped, as are any
+        // we do not understand
+        if self.state != State::Running {
+            return Ok(());
+        }
+
+        let Notification { method, params } = not;
+        match method.as_str() {
+            notification::DidOpenTextDocument::METHOD => self
+                .dispatch_notification::<notification::DidOpenTextDocument>(params, Self::did_open),
+            notification::DidChangeTextDocument::METHOD => self
+                .dispatch_notification::<notification::DidChangeTextDocument>(
+                    params,
+                    Self::did_change,
+                ),
+            notification::DidCloseTextDocument::METHOD => self
+                .dispatch_notification::<notification::DidCloseTextDocument>(
+                    params,
+                    Self::did_close,
+                ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Decodes the parameters of a request, invokes its handler and encodes
+    /// the result
+    fn dispatch<R: LspRequest>(
+        &mut self,
+        params: Value,
+        handler: fn(&mut Self, R::Params) -> HandlerResult<R::Result>,
+    ) -> HandlerResult<Value> {
+        let params = parse_params::<R::Params>(params)?;
+        let result = handler(self, params)?;
+        Ok(serde_json::to_value(result).unwrap())
+    }
+
+    /// Decodes the parameters of a notification and invokes its handler.
+    /// There is no way to report an error for a notification back to the
+    /// client, so malformed parameters are only logged
+    fn dispatch_notification<N: LspNotification>(
+        &mut self,
+        params: Value,
+        handler: fn(&mut Self, N::Params) -> io::Result<()>,
+    ) -> io::Result<()> {
+        match parse_params::<N::Params>(params) {
+            Ok(params) => handler(self, params),
+            Err(e) => self.log_message(
+                MessageType::ERROR,
+                format!("invalid params for {}: {}", N::METHOD, e.message),
+            ),
+        }
+    }
+}
+
+fn parse_params<P: DeserializeOwned>(params: Value) -> HandlerResult<P> {
+    serde_json::from_value(params)
+        .map_err(|e| ResponseError::new(ErrorCode::InvalidParams, e.to_string()))
+}
+
+impl<W: Write> Server<W> {
+    fn initialize(&mut self, params: InitializeParams) -> HandlerResult<InitializeResult> {
+        self.state = State::Running;
+        Ok(InitializeResult {
+            capabilities: capabilities(),
+            server_info: Some(ServerInfo {
+                name: env!("CARGO_PKG_NAME").to_owned(),
+                version: Some(env!("CARGO_PKG_VERSION").to_owned()),
+            }),
+        })
+    }
+
+    fn shutdown(&mut self, params: ()) -> HandlerResult<()> {
+        self.state = State::ShuttingDown;
+        Ok(())
+    }
+}
+
+impl<W: Write> Server<W> {
+    fn did_open(&mut self, params: DidOpenTextDocumentParams) -> io::Result<()> {
+        let doc = params.text_document;
+        self.documents
+            .insert(doc.uri.clone(), Document::new(doc.text, doc.version));
+        self.sync_document(&doc.uri);
+        self.sync_files();
+        self.publish_all_diagnostics(doc.uri)
+    }
+
+    fn did_change(&mut self, params: DidChangeTextDocumentParams) -> io::Result<()> {
+        let uri = params.text_document.uri;
+        let document = match self.documents.get_mut(&uri) {
+            Some(document) => document,
+            None => {
+                return self.log_message(
+                    MessageType::WARNING,
+                    format!("change to unopened document {}", uri),
+                );
+            }
+        };
+
+        for change in params.content_changes {
+            document.apply_change(change);
+        }
+        document.set_version(params.text_document.version);
+        self.sync_document(&uri);
+        self.publish_all_diagnostics(uri)
+    }
+
+    fn did_close(&mut self, params: DidCloseTextDocumentParams) -> io::Result<()> {
+        let uri = params.text_document.uri;
+        self.documents.remove(&uri);
+        self.published.remove(&uri);
+        self.sync_files();
+        // the client keeps showing whatever we last published until told
+        // otherwise
+        self.send_notification::<notification::PublishDiagnostics>(PublishDiagnosticsParams {
+            uri,
+            diagnostics: Vec::new(),
+            version: None,
+        })?;
+        self.publish_changed_diagnostics(None)
+    }
+
+    /// Copies the text of an open document into the database
+    fn sync_document(&mut self, uri: &Url) {
+        let document = &self.documents[uri];
+        let next_id = FileId(self.file_ids.len() as u32);
+        let file = *self.file_ids.entry(uri.clone()).or_insert(next_id);
+        self.db
+            .set_file_text(file, Rc::new(document.text().to_owned()));
+    }
+
+    /// Tells the database which documents are currently open
+    fn sync_files(&mut self) {
+        let mut files: Vec<_> = self
+            .documents
+            .keys()
+            .map(|uri| self.file_ids[uri])
+            .collect();
+        files.sort();
+        self.db.set_files(Rc::new(files));
+    }
+
+    /// Publishes the diagnostics of `uri`, which has just been opened or
+    /// edited, followed by those of any other open document they changed for
+    fn publish_all_diagnostics(&mut self, uri: Url) -> io::Result<()> {
+        self.publish_diagnostics(uri.clone())?;
+        self.publish_changed_diagnostics(Some(&uri))
+    }
+
+    /// Republishes the diagnostics of every open document other than
+    /// `except` whose diagnostics differ from those last published. Errors
+    /// such as duplicate classes span several files, so an edit to one file
+    /// can add or remove errors in another
+    fn publish_changed_diagnostics(&mut self, except: Option<&Url>) -> io::Result<()> {
+        let mut uris: Vec<Url> = self
+            .documents
+            .keys()
+            .filter(|&uri| Some(uri) != except)
+            .cloned()
+            .collect();
+        uris.sort();
+        for uri in uris {
+            let diagnostics = self.db.diagnostics(self.file_ids[&uri]);
+            if self.published.get(&uri) != Some(&diagnostics) {
+                self.publish_diagnostics(uri)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn publish_diagnostics(&mut self, uri: Url) -> io::Result<()> {
+        let document = match self.documents.get(&uri) {
+            Some(document) => document,
+            None => return Ok(()),
+        };
+
+        let file = self.file_ids[&uri];
+        let published = self.db.diagnostics(file);
+        let diagnostics = published
+            .iter()
+            .map(|diag| convert::diagnostic(&uri, document.line_index(), diag))
+            .collect();
+        self.published.insert(uri.clone(), published);
+        let version = Some(document.version());
+
+        self.send_notification::<notification::PublishDiagnostics>(PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version,
+        })
+    }
+}
+
+/// The features this server advertises to the client in response to
+/// `initialize`
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::INCREMENTAL),
+                ..TextDocumentSyncOptions::default()
+            },
+        )),
+        ..ServerCapabilities::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use transport::RequestId;
+
+    fn request(id: i64, method: &str, params: Value) -> Message {
+        Message::Request(Request {
+            id: RequestId::Number(id),
+            method: method.to_owned(),
+            params,
+        })
+    }
+
+    fn notification(method: &str, params: Value) -> Message {
+        Message::Notification(Notification {
+            method: method.to_owned(),
+            params,
+        })
+    }
+
+    /// Feeds `messages` to a fresh server, returning its exit code and
+    /// everything it sent back
+    fn session(messages: &[Message]) -> (i32, Vec<Message>) {
+        let mut input = Vec::new();
+        for msg in messages {
+            transport::write_message(&mut input, msg).unwrap();
+        }
+
+        let mut server = Server::new(Vec::new());
+        let code = server.run(&mut input.as_slice()).unwrap();
+
+        let mut output = server.writer.as_slice();
+        let mut sent = Vec::new();
+        while let Some(msg) = transport::read_message(&mut output).unwrap() {
+            sent.push(msg);
+        }
+        (code, sent)
+    }
+
+    fn initialize() -> Message {
+        request(1, "initialize", json!({ "capabilities": {} }))
+    }
+
+    fn response(msg: &Message) -> &Response {
+        match msg {
+            Message::Response(resp) => resp,
+            msg => panic!("expected response, got {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn clean_lifecycle() {
+        let (code, sent) = session(&[
+            initialize(),
+            notification("initialized", json!({})),
+            request(2, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ]);
+        assert_eq!(code, 0);
+        assert_eq!(sent.len(), 2);
+
+        let init = response(&sent[0]);
+        assert_eq!(init.id, Some(RequestId::Number(1)));
+        assert_eq!(
+            init.result.as_ref().unwrap()["serverInfo"]["name"],
+            "groovy-lsp"
+        );
+
+        let shutdown = response(&sent[1]);
+        assert_eq!(shutdown.id, Some(RequestId::Number(2)));
+        assert_eq!(shutdown.error, None);
+    }
+
+    #[test]
+    fn exit_without_shutdown_is_an_error() {
+        let (code, _) = session(&[initialize(), notification("exit", Value::Null)]);
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn requests_before_initialize_are_rejected() {
+        let (_, sent) = session(&[request(1, "textDocument/hover", json!({}))]);
+        let error = response(&sent[0]).error.as_ref().unwrap();
+        assert_eq!(error.code, ErrorCode::ServerNotInitialized as i32);
+    }
+
+    #[test]
+    fn unknown_methods_and_bad_params() {
+        let (_, sent) = session(&[
+            initialize(),
+            request(2, "groovy/unknown", Value::Null),
+            request(3, "shutdown", json!({ "unexpected": true })),
+        ]);
+        let error = response(&sent[1]).error.as_ref().unwrap();
+        assert_eq!(error.code, ErrorCode::MethodNotFound as i32);
+        let error = response(&sent[2]).error.as_ref().unwrap();
+        assert_eq!(error.code, ErrorCode::InvalidParams as i32);
+    }
+
+    #[test]
+    fn problems_with_notifications_are_logged() {
+        let (_, sent) = session(&[
+            initialize(),
+            notification("textDocument/didOpen", json!({ "unexpected": true })),
+            notification(
+                "textDocument/didChange",
+                json!({
+                    "textDocument": { "uri": "file:///project/A.groovy", "version": 2 },
+                    "contentChanges": [{ "text": "class A {}" }]
+                }),
+            ),
+        ]);
+        let logged: Vec<LogMessageParams> = sent[1..]
+            .iter()
+            .map(|msg| match msg {
+                Message::Notification(not) if not.method == "window/logMessage" => {
+                    serde_json::from_value(not.params.clone()).unwrap()
+                }
+                msg => panic!("expected log message, got {:?}", msg),
+            })
+            .collect();
+        assert_eq!(logged.len(), 2);
+        assert_eq!(logged[0].typ, MessageType::ERROR);
+        assert!(logged[0]
+            .message
+            .starts_with("invalid params for textDocument/didOpen"));
+        assert_eq!(logged[1].typ, MessageType::WARNING);
+        assert_eq!(
+            logged[1].message,
+            "change to unopened document file:///project/A.groovy"
+        );
+    }
+
+    #[test]
+    fn undecodable_messages_are_answered_with_parse_errors() {
+        let mut input = Vec::new();
+        transport::write_message(&mut input, &initialize()).unwrap();
+        let truncated = r#"{"jsonrpc":"2.0","id":2,"method":"shut"#;
+        write!(
+            input,
+            "Content-Length: {}\r\n\r\n{}",
+            truncated.len(),
+            truncated
+        )
+        .unwrap();
+        transport::write_message(&mut input, &request(3, "shutdown", Value::Null)).unwrap();
+
+        let mut server = Server::new(Vec::new());
+        assert_eq!(server.run(&mut input.as_slice()).unwrap(), 1);
+        let mut output = server.writer.as_slice();
+        let mut sent = Vec::new();
+        while let Some(msg) = transport::read_message(&mut output).unwrap() {
+            sent.push(msg);
+        }
+
+        assert_eq!(sent.len(), 3, "{:?}", sent);
+        let parse_error = response(&sent[1]);
+        assert_eq!(parse_error.id, None);
+        assert_eq!(
+            parse_error.error.as_ref().unwrap().code,
+            ErrorCode::ParseError as i32
+        );
+        let shutdown = response(&sent[2]);
+        assert_eq!(shutdown.id, Some(RequestId::Number(3)));
+        assert!(shutdown.error.is_none());
+    }
+
+    fn did_open(uri: &str, text: &str) -> Message {
+        notification(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": { "uri": uri, "languageId": "groovy", "version": 1, "text": text }
+            }),
+        )
+    }
+
+    fn published(msg: &Message) -> PublishDiagnosticsParams {
+        match msg {
+            Message::Notification(not) if not.method == "textDocument/publishDiagnostics" => {
+                serde_json::from_value(not.params.clone()).unwrap()
+            }
+            msg => panic!("expected diagnostics, got {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn publishes_diagnostics_for_open_documents() {
+        let uri = "file:///project/A.groovy";
+        let (_, sent) = session(&[
+            initialize(),
+            did_open(uri, "class A {\n  void f(int a) {\n"),
+            notification(
+                "textDocument/didChange",
+                json!({
+                    "textDocument": { "uri": uri, "version": 2 },
+                    "contentChanges": [
+                        {
+                            "range": {
+                                "start": { "line": 2, "character": 0 },
+                                "end": { "line": 2, "character": 0 }
+                            },
+                            "text": "  }\n"
+                        },
+                        {
+                            "range": {
+                                "start": { "line": 3, "character": 0 },
+                                "end": { "line": 3, "character": 0 }
+                            },
+                            "text": "}"
+                        }
+                    ]
+                }),
+            ),
+            notification(
+                "textDocument/didClose",
+                json!({ "textDocument": { "uri": uri } }),
+            ),
+        ]);
+        assert_eq!(sent.len(), 4);
+
+        let opened = published(&sent[1]);
+        assert_eq!(opened.uri.as_str(), uri);
+        assert_eq!(opened.version, Some(1));
+        assert_eq!(opened.diagnostics.len(), 2);
+        assert_eq!(
+            opened.diagnostics[0].range.start,
+            lsp_types::Position::new(2, 0)
+        );
+
+        let changed = published(&sent[2]);
+        assert_eq!(changed.version, Some(2));
+        assert!(changed.diagnostics.is_empty());
+
+        let closed = published(&sent[3]);
+        assert!(closed.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn republishes_diagnostics_of_other_documents() {
+        let a = "file:///project/A.groovy";
+        let b = "file:///project/B.groovy";
+        let (_, sent) = session(&[
+            initialize(),
+            did_open(a, "class Util {}"),
+            did_open(b, "class Util {}"),
+            notification(
+                "textDocument/didClose",
+                json!({ "textDocument": { "uri": b } }),
+            ),
+        ]);
+        assert_eq!(sent.len(), 6, "{:?}", sent);
+        assert!(published(&sent[1]).diagnostics.is_empty());
+
+        // opening B makes the class in A a duplicate
+        let opened = published(&sent[2]);
+        assert_eq!(opened.uri.as_str(), b);
+        assert_eq!(opened.diagnostics.len(), 1);
+        let other = published(&sent[3]);
+        assert_eq!(other.uri.as_str(), a);
+        assert_eq!(other.diagnostics.len(), 1);
+
+        // and closing it clears the error again
+        assert_eq!(published(&sent[4]).uri.as_str(), b);
+        let cleared = published(&sent[5]);
+        assert_eq!(cleared.uri.as_str(), a);
+        assert!(cleared.diagnostics.is_empty());
+    }
+}
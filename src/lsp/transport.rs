@@ -0,0 +1,293 @@
+//! JSON-RPC 2.0 messages and the `Content-Length` framing used to send them
+//! over stdio
+
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(i64),
+    String(String),
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RequestId::Number(n) => write!(f, "{}", n),
+            RequestId::String(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+/// A single JSON-RPC message. The variants are distinguished by which of the
+/// `id` and `method` fields are present, so the order here matters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Message {
+    Request(Request),
+    Notification(Notification),
+    Response(Response),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub id: RequestId,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub id: Option<RequestId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ResponseError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseError {
+    pub code: i32,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// Error codes defined by JSON-RPC and the Language Server Protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    ParseError = -32700,
+    InvalidRequest = -32600,
+    MethodNotFound = -32601,
+    InvalidParams = -32602,
+    InternalError = -32603,
+    ServerNotInitialized = -32002,
+    RequestCancelled = -32800,
+}
+
+impl ResponseError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code: code as i32,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl Response {
+    pub fn ok(id: RequestId, result: Value) -> Self {
+        Self {
+            id: Some(id),
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn err(id: Option<RequestId>, error: ResponseError) -> Self {
+        Self {
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+impl Notification {
+    pub fn new(method: impl Into<String>, params: impl Serialize) -> Self {
+        Self {
+            method: method.into(),
+            params: serde_json::to_value(params).unwrap(),
+        }
+    }
+}
+
+/// Every outgoing message carries the protocol version alongside its fields
+#[derive(Serialize)]
+struct JsonRpc<'a> {
+    jsonrpc: &'static str,
+    #[serde(flatten)]
+    msg: &'a Message,
+}
+
+/// Reads the next message, returning `Ok(None)` once the input is closed. A
+/// message which cannot be decoded is an `InvalidData` error; its headers and
+/// content are still consumed where possible, so that the next message can be
+/// read as usual
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Message>> {
+    let mut content_length = None;
+    let mut saw_header = false;
+    let mut error = None;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let header = line.trim_end_matches(&['\r', '\n'][..]);
+        if header.is_empty() {
+            // a blank line separates the headers from the content. tolerate
+            // stray blank lines before the headers begin
+            if saw_header {
+                break;
+            }
+            continue;
+        }
+        saw_header = true;
+
+        let (name, value) = match header.find(':') {
+            Some(idx) => (&header[..idx], header[idx + 1..].trim()),
+            None => {
+                error.get_or_insert_with(|| format!("malformed header: {:?}", header));
+                continue;
+            }
+        };
+
+        if name.eq_ignore_ascii_case("Content-Length") {
+            match value.parse::<usize>() {
+                Ok(len) => content_length = Some(len),
+                Err(_) => {
+                    error.get_or_insert_with(|| format!("invalid Content-Length: {:?}", value));
+                }
+            }
+        }
+    }
+
+    let content = match content_length {
+        Some(len) => {
+            let mut content = vec![0; len];
+            reader.read_exact(&mut content)?;
+            content
+        }
+        None => {
+            return Err(invalid_data(
+                error.unwrap_or_else(|| "missing Content-Length".to_owned()),
+            ))
+        }
+    };
+    if let Some(error) = error {
+        return Err(invalid_data(error));
+    }
+
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| invalid_data(format!("malformed message: {}", e)))
+}
+
+pub fn write_message(writer: &mut impl Write, msg: &Message) -> io::Result<()> {
+    let content = serde_json::to_string(&JsonRpc {
+        jsonrpc: "2.0",
+        msg,
+    })?;
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn frame(content: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+    }
+
+    #[test]
+    fn reads_each_message_kind() {
+        let input = [
+            frame(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#),
+            frame(r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#),
+            frame(r#"{"jsonrpc":"2.0","id":"a","result":null}"#),
+        ]
+        .concat();
+        let mut reader = input.as_bytes();
+
+        match read_message(&mut reader).unwrap() {
+            Some(Message::Request(req)) => {
+                assert_eq!(req.id, RequestId::Number(1));
+                assert_eq!(req.method, "initialize");
+            }
+            msg => panic!("expected request, got {:?}", msg),
+        }
+        match read_message(&mut reader).unwrap() {
+            Some(Message::Notification(not)) => assert_eq!(not.method, "initialized"),
+            msg => panic!("expected notification, got {:?}", msg),
+        }
+        match read_message(&mut reader).unwrap() {
+            Some(Message::Response(resp)) => {
+                assert_eq!(resp.id, Some(RequestId::String("a".to_owned())))
+            }
+            msg => panic!("expected response, got {:?}", msg),
+        }
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn content_length_counts_bytes() {
+        let msg = Message::Notification(Notification::new("test", json!({ "text": "é" })));
+        let mut out = Vec::new();
+        write_message(&mut out, &msg).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let (header, content) = out.split_at(out.find("\r\n\r\n").unwrap() + 4);
+        assert_eq!(header, format!("Content-Length: {}\r\n\r\n", content.len()));
+        assert!(content.contains(r#""jsonrpc":"2.0""#));
+
+        assert_eq!(read_message(&mut out.as_bytes()).unwrap(), Some(msg));
+    }
+
+    #[test]
+    fn bad_messages_are_skipped() {
+        let input = [
+            "Content-Length: 2\r\nbogus\r\n\r\n{}".to_owned(),
+            frame(r#"{"jsonrpc":"2.0","id":1,"#),
+            frame(r#"{"jsonrpc":"2.0","method":"initialized"}"#),
+        ]
+        .concat();
+        let mut reader = input.as_bytes();
+
+        let err = read_message(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), r#"malformed header: "bogus""#);
+        let err = read_message(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        match read_message(&mut reader).unwrap() {
+            Some(Message::Notification(not)) => assert_eq!(not.method, "initialized"),
+            msg => panic!("expected notification, got {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn missing_content_length_is_an_error() {
+        let mut reader = "Content-Type: foo\r\n\r\n{}".as_bytes();
+        assert!(read_message(&mut reader).is_err());
+
+        let mut reader = "Content-Length: abc\r\n\r\n{}".as_bytes();
+        assert!(read_message(&mut reader).is_err());
+    }
+}
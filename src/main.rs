@@ -1,17 +1,18 @@
-#![feature(or_patterns)]
 #![feature(negative_impls)]
 #![allow(dead_code, unused_variables, unused_mut, unused_imports)]
 
-use std::fs;
-
-use parse::GroovyParser;
-
 mod ast;
+mod db;
+mod diagnostic;
 mod interner;
+mod items;
 mod lexer;
+mod lsp;
 mod parse;
+mod scope;
+mod span;
+mod syntax;
 
 fn main() {
-    let input = fs::read_to_string("test.groovy").unwrap();
-    dbg!(GroovyParser::new(&input)).unwrap();
+    std::process::exit(lsp::run_stdio());
 }
@@ -1,73 +1,797 @@
-use std::{convert::TryFrom, iter::Peekable};
+use std::convert::TryFrom;
+
+use rowan::GreenNode;
 
 use crate::{
     ast::{
-        AstNode, BinaryOperator, Class, ClassModifier, ConstExpr, Expr, Identifier, Literal,
-        Method, MethodModifier, Parameter, Stmt, Token, Type, Variable, Visibility,
+        Annotation, AnnotationElement, AnnotationValue, AssignmentOperator, AstNode,
+        BinaryOperator, Class, ClassKind, ClassModifier, CompilationUnit, ConstExpr, EnumConstant,
+        Expr, ExprKind, Field, GString, Identifier, Import, Initializer, InterpolatedStringPart,
+        Literal, Method, MethodModifier, Package, Parameter, Property, QualifiedName, SpannedToken,
+        Stmt, StmtKind, Token, Type, TypeParameter, UnaryOperator, Variable, Visibility,
+        WildcardBound,
     },
+    diagnostic::{Diagnostic, ErrorCode, Severity},
     interner::keywords::Keywords,
-    lexer::GroovyLexer,
+    lexer::{GroovyLexer, Lexed},
+    span::{Span, Spanned},
+    syntax::{self, CstNode, Event, SourceFile, SyntaxKind, SyntaxNode},
 };
 
 const TODO_BOOL: bool = false;
 
 pub struct GroovyParser<'a> {
-    lexer: Peekable<GroovyLexer<'a>>,
+    input: &'a str,
+    tokens: Vec<SpannedToken>,
+    /// Index of the next token to be consumed
+    pos: usize,
+    /// The span of the most recently consumed token
+    prev_span: Span,
+    diagnostics: Vec<Diagnostic>,
+    doc_comments: Vec<Spanned<String>>,
+    /// The shape of the syntax tree, built alongside the AST
+    events: Vec<Event>,
+    /// Shift operators which were split into `>` tokens to close type
+    /// arguments, with their index, so that the split can be undone
+    splits: Vec<(usize, SpannedToken)>,
+}
+
+/// The state of the parser, saved before looking ahead speculatively
+struct Snapshot {
+    pos: usize,
+    prev_span: Span,
+    events: usize,
+    splits: usize,
+    diagnostics: usize,
+}
+
+/// The result of parsing a file. Parsing always produces a tree, even if the
+/// input is malformed; the parts which could not be understood are replaced
+/// with error nodes and described in `diagnostics`
+#[derive(Debug, PartialEq, Eq)]
+pub struct Parse {
+    pub unit: CompilationUnit,
+    pub diagnostics: Vec<Diagnostic>,
+    /// The lossless syntax tree of the file
+    pub green: GreenNode,
+}
+
+impl Parse {
+    pub fn syntax(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    pub fn source_file(&self) -> SourceFile {
+        SourceFile::cast(self.syntax()).unwrap()
+    }
 }
 
 impl<'a> GroovyParser<'a> {
-    pub fn new(input: &'a str) -> GResult<Vec<AstNode>> {
+    pub fn parse(input: &'a str) -> Parse {
+        Self::parse_tokens(input, GroovyLexer::tokenize(input))
+    }
+
+    /// Parses tokens which have already been lexed from `input`
+    pub fn parse_tokens(input: &'a str, lexed: Lexed) -> Parse {
         let mut parser = Self {
-            lexer: GroovyLexer::new(input).peekable(),
+            input,
+            tokens: lexed.tokens,
+            pos: 0,
+            prev_span: Span::default(),
+            diagnostics: lexed.diagnostics,
+            doc_comments: lexed.doc_comments,
+            events: Vec::new(),
+            splits: Vec::new(),
         };
 
-        Ok(vec![parser.parse_toplevel()?])
+        let mut unit = CompilationUnit {
+            package: None,
+            imports: Vec::new(),
+            types: Vec::new(),
+            statements: Vec::new(),
+            methods: Vec::new(),
+            span: Span::new(0, input.len()),
+        };
+        loop {
+            parser.skip_newlines();
+            if parser.peek_token().is_none() {
+                break;
+            }
+            parser.parse_toplevel(&mut unit);
+        }
+
+        Parse {
+            unit,
+            green: syntax::build_tree(input, &parser.tokens, parser.events),
+            diagnostics: parser.diagnostics,
+        }
     }
-}
 
-#[derive(Debug)]
-pub struct GroovyError {
-    message: &'static str,
-}
+    fn peek_token(&self) -> Option<&Token> {
+        self.peek_nth(0)
+    }
+
+    /// Looks `n` tokens past the next one without consuming anything
+    fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n).map(|tok| &tok.token)
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let SpannedToken { token, span } = self.tokens.get(self.pos)?.clone();
+        self.pos += 1;
+        self.prev_span = span;
+        self.events.push(Event::Token);
+        Some(token)
+    }
+
+    /// Consumes a keyword. Contextual keywords are lexed as identifiers, but
+    /// still appear as keywords in the syntax tree
+    fn next_keyword(&mut self) {
+        if let Some(Token::Identifier(..)) = self.next_token() {
+            *self.events.last_mut().unwrap() = Event::ContextualKeyword;
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pos: self.pos,
+            prev_span: self.prev_span,
+            events: self.events.len(),
+            splits: self.splits.len(),
+            diagnostics: self.diagnostics.len(),
+        }
+    }
+
+    /// Rewinds to `snapshot`, as if nothing since had been parsed
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.pos = snapshot.pos;
+        self.prev_span = snapshot.prev_span;
+        self.events.truncate(snapshot.events);
+        self.diagnostics.truncate(snapshot.diagnostics);
+        while self.splits.len() > snapshot.splits {
+            let (idx, token) = self.splits.pop().unwrap();
+            self.tokens.remove(idx + 1);
+            self.tokens[idx] = token;
+        }
+    }
+
+    /// Marks the current position in the syntax tree, so that a node can
+    /// later be started there
+    fn checkpoint(&mut self) -> usize {
+        self.events.push(Event::Start {
+            kind: None,
+            forward_parent: None,
+        });
+        self.events.len() - 1
+    }
+
+    /// Wraps everything parsed since `checkpoint` in a syntax node. When a
+    /// node was already started at `checkpoint`, the new one is recorded as
+    /// its forward parent rather than inserted before it
+    fn finish_node_at(&mut self, checkpoint: usize, kind: SyntaxKind) {
+        let len = self.events.len();
+        let mut idx = checkpoint;
+        loop {
+            let Event::Start {
+                kind: started,
+                forward_parent,
+            } = &mut self.events[idx]
+            else {
+                unreachable!("a checkpoint is always the start of a node");
+            };
+            match (*started, *forward_parent) {
+                (None, _) => {
+                    *started = Some(kind);
+                    break;
+                }
+                (Some(_), Some(distance)) => idx += distance,
+                (Some(_), None) => {
+                    *forward_parent = Some(len - idx);
+                    self.events.push(Event::Start {
+                        kind: Some(kind),
+                        forward_parent: None,
+                    });
+                    break;
+                }
+            }
+        }
+        self.events.push(Event::Finish);
+    }
+
+    /// The span of the next token, or an empty span at the end of the input if
+    /// there are no tokens left
+    fn peek_span(&self) -> Span {
+        match self.tokens.get(self.pos) {
+            Some(tok) => tok.span,
+            None => Span::empty(self.input.len()),
+        }
+    }
+
+    /// The span from `start` to the end of the most recently consumed token
+    fn span_from(&self, start: usize) -> Span {
+        Span::new(start, self.prev_span.end.max(start))
+    }
+
+    /// Skips any `;` or line breaks, which are allowed between declarations
+    /// and statements
+    fn skip_newlines(&mut self) {
+        while let Some(Token::NewLine | Token::ExprEnd) = self.peek_token() {
+            self.next_token();
+        }
+    }
+
+    /// The next token which is not a line break
+    fn peek_past_newlines(&self) -> Option<&Token> {
+        let mut n = 0;
+        while self.peek_nth(n) == Some(&Token::NewLine) {
+            n += 1;
+        }
+        self.peek_nth(n)
+    }
+
+    /// Whether a block follows, possibly on the next line
+    fn at_block(&self) -> bool {
+        self.peek_past_newlines() == Some(&Token::CurlyBraceOpen)
+    }
+
+    /// Whether a line break separates the next token from the previous one
+    fn at_line_start(&self) -> bool {
+        let span = self.peek_span();
+        self.pos == 0 || self.input[self.prev_span.end..span.start].contains('\n')
+    }
+
+    /// The Groovydoc comment directly preceding the next token, if there is
+    /// one with no other tokens in between
+    fn doc_comment(&self) -> Option<String> {
+        let before = self.peek_span().start;
+        let after = match self.pos {
+            0 => 0,
+            pos => self.tokens[pos - 1].span.end,
+        };
+        let idx = self
+            .doc_comments
+            .partition_point(|doc| doc.span.end <= before);
+        let doc = self.doc_comments[..idx].last()?;
+        (doc.span.start >= after).then(|| doc.node.clone())
+    }
+
+    /// An error stating that `expected` was wanted in place of the next token
+    fn expected(&self, expected: &str) -> Diagnostic {
+        match self.peek_token() {
+            Some(found) => Diagnostic::error(
+                ErrorCode::UnexpectedToken,
+                self.peek_span(),
+                format!("expected {}, found {}", expected, found),
+            ),
+            None => Diagnostic::error(
+                ErrorCode::MissingSyntax,
+                self.peek_span(),
+                format!("expected {}, found end of file", expected),
+            ),
+        }
+    }
+
+    /// A note for syntax which is valid Groovy but which we cannot parse yet.
+    /// The code is not wrong, so this is information rather than an error
+    fn unsupported(&self, what: &str) -> Diagnostic {
+        Diagnostic::new(
+            Severity::Information,
+            ErrorCode::Unsupported,
+            self.peek_span(),
+            format!("{} are not supported yet", what),
+        )
+    }
+
+    /// Skips the bracketed group beginning at the next token, up to and
+    /// including the bracket which closes it
+    fn skip_group(&mut self) {
+        let mut depth = 0_usize;
+        loop {
+            match self.next_token() {
+                None => break,
+                Some(
+                    Token::ParenOpen
+                    | Token::SquareBraceOpen
+                    | Token::SafeIndex
+                    | Token::CurlyBraceOpen
+                    | Token::GStringInterpolationStart,
+                ) => depth += 1,
+                Some(Token::ParenClose | Token::SquareBraceClose | Token::CurlyBraceClose) => {
+                    depth = depth.saturating_sub(1)
+                }
+                Some(..) => {}
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+
+    fn at_group_start(&self) -> bool {
+        matches!(
+            self.peek_token(),
+            Some(
+                Token::ParenOpen
+                    | Token::SquareBraceOpen
+                    | Token::SafeIndex
+                    | Token::CurlyBraceOpen
+                    | Token::GStringInterpolationStart
+            )
+        )
+    }
+
+    /// Records that the statement at the next token is not supported, and
+    /// skips all of it, including any `catch` or `finally` on later lines,
+    /// so that no errors follow from it
+    fn skip_unsupported_stmt(&mut self, what: &str) {
+        self.diagnostics.push(self.unsupported(what));
+        loop {
+            match self.peek_token() {
+                None | Some(Token::ExprEnd | Token::CurlyBraceClose) => break,
+                Some(Token::NewLine) => {
+                    if !matches!(
+                        self.peek_past_newlines(),
+                        Some(Token::Keyword(Keywords::Catch | Keywords::Finally))
+                    ) {
+                        break;
+                    }
+                    self.skip_newlines();
+                }
+                Some(..) if self.at_group_start() => self.skip_group(),
+                Some(..) => {
+                    self.next_token();
+                }
+            }
+        }
+    }
+
+    /// Records that the expression at the next token is not supported, and
+    /// skips it: a closure, list or map as a whole, or `new` along with its
+    /// type, arguments and any anonymous class body
+    fn skip_unsupported_expr(&mut self, what: &str) {
+        self.diagnostics.push(self.unsupported(what));
+        if self.at_group_start() {
+            self.skip_group();
+            return;
+        }
+        let is_new = self.next_token() == Some(Token::Keyword(Keywords::New));
+        if is_new {
+            while let Some(
+                Token::Identifier(..)
+                | Token::Keyword(..)
+                | Token::Period
+                | Token::Lt
+                | Token::Gt
+                | Token::Shr
+                | Token::QuestionMark
+                | Token::Comma,
+            ) = self.peek_token()
+            {
+                self.next_token();
+            }
+            while let Some(Token::ParenOpen | Token::SquareBraceOpen) = self.peek_token() {
+                self.skip_group();
+            }
+            if self.peek_token() == Some(&Token::CurlyBraceOpen) {
+                self.skip_group();
+            }
+        } else if self.peek_token() == Some(&Token::ParenOpen) {
+            // `this(...)` or `super(...)` calling another constructor
+            self.skip_group();
+        }
+    }
+
+    /// An error for a `{` which reached the end of the file without being
+    /// closed
+    fn unclosed_brace(&self, open: Span) -> Diagnostic {
+        let end = self.peek_span();
+        Diagnostic::error(
+            ErrorCode::UnclosedDelimiter,
+            end,
+            "expected `}`, found end of file",
+        )
+        .with_label(open, "unclosed delimiter")
+        .with_suggestion("insert the missing `}`", end, "}")
+    }
+
+    /// Records `err` and skips ahead to a point where parsing can sensibly
+    /// resume: just past a `;`, before a `}` closing the enclosing block,
+    /// before a line break or the first token on a new line, or before a
+    /// keyword which can only begin a declaration. At least one token is
+    /// skipped if nothing has been consumed since `start_pos`, so that
+    /// callers looping over declarations always make progress
+    fn recover(&mut self, err: Diagnostic, start_pos: usize) {
+        self.diagnostics.push(err);
+
+        if self.pos == start_pos {
+            match self.peek_token() {
+                Some(Token::CurlyBraceClose) | None => return,
+                Some(Token::CurlyBraceOpen) => {}
+                Some(..) => {
+                    self.next_token();
+                }
+            }
+        }
 
-impl GroovyError {
-    pub fn new(message: &'static str) -> Self {
-        Self { message }
+        let mut depth = 0_usize;
+        loop {
+            match self.peek_token() {
+                None => break,
+                Some(Token::CurlyBraceClose) if depth == 0 => break,
+                Some(Token::NewLine) if depth == 0 => break,
+                Some(Token::ExprEnd) if depth == 0 => {
+                    self.next_token();
+                    break;
+                }
+                Some(Token::Keyword(
+                    Keywords::Class
+                    | Keywords::Interface
+                    | Keywords::Enum
+                    | Keywords::Public
+                    | Keywords::Private
+                    | Keywords::Protected,
+                )) if depth == 0 => break,
+                Some(..) if depth == 0 && self.pos != start_pos && self.at_line_start() => break,
+                Some(Token::CurlyBraceOpen | Token::GStringInterpolationStart) => depth += 1,
+                Some(Token::CurlyBraceClose) => depth -= 1,
+                Some(..) => {}
+            }
+            self.next_token();
+        }
     }
 }
 
-type GResult<T> = Result<T, GroovyError>;
+type GResult<T> = Result<T, Diagnostic>;
 
 impl<'a> GroovyParser<'a> {
-    fn parse_toplevel(&mut self) -> GResult<AstNode> {
+    /// Parses a single declaration or statement at the top level of a file,
+    /// adding it to `unit`. Anything which cannot be parsed is kept as an
+    /// error statement
+    fn parse_toplevel(&mut self, unit: &mut CompilationUnit) {
+        let start_pos = self.pos;
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+
+        // packages and imports may be annotated, as in `@Grab('a:b:1') import b.C`
+        let annotations_len = self.annotations_len();
+        let result = match self.peek_token().cloned() {
+            Some(Token::CurlyBraceClose) => {
+                let span = self.peek_span();
+                self.diagnostics.push(
+                    Diagnostic::error(ErrorCode::UnmatchedDelimiter, span, "unmatched `}`")
+                        .with_suggestion("remove the `}`", span, ""),
+                );
+                self.next_token();
+                self.finish_node_at(checkpoint, SyntaxKind::Error);
+                unit.statements
+                    .push(Stmt::new(StmtKind::Error, self.span_from(start)));
+                return;
+            }
+            _ if self.peek_nth(annotations_len) == Some(&Token::Keyword(Keywords::Package)) => {
+                self.parse_package(unit)
+            }
+            _ if self.peek_nth(annotations_len) == Some(&Token::Keyword(Keywords::Import)) => {
+                self.parse_import().map(|import| unit.imports.push(import))
+            }
+            _ if self.at_type_declaration() => self.parse_type_declaration().map(|node| {
+                if let AstNode::Class(class) = node {
+                    unit.types.push(class);
+                }
+            }),
+            // script methods are declared just like the methods of a class
+            _ if self.at_method_declaration() => self
+                .parse_script_method()
+                .map(|method| unit.methods.push(method)),
+            _ => {
+                unit.statements.push(self.parse_stmt());
+                return;
+            }
+        };
+
+        if let Err(err) = result {
+            self.recover(err, start_pos);
+            self.finish_node_at(checkpoint, SyntaxKind::Error);
+            unit.statements
+                .push(Stmt::new(StmtKind::Error, self.span_from(start)));
+        }
+    }
+
+    fn parse_package(&mut self, unit: &mut CompilationUnit) -> GResult<()> {
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        let annotations = self.parse_annotations()?;
+        self.next_token();
+        let name = self.parse_qualified_name()?;
+        self.expect_stmt_end()?;
+        self.finish_node_at(checkpoint, SyntaxKind::PackageDecl);
+
+        let package = Package {
+            name,
+            annotations,
+            span: self.span_from(start),
+        };
+        match &unit.package {
+            Some(previous) => self.diagnostics.push(
+                Diagnostic::error(
+                    ErrorCode::DuplicateDefinition,
+                    package.span,
+                    "a file can only declare one package",
+                )
+                .with_label(previous.span, "previously declared here"),
+            ),
+            None => unit.package = Some(package),
+        }
+        Ok(())
+    }
+
+    /// Parses `import a.b.C`, optionally `static`, ending in `.*` or renamed
+    /// with `as`
+    fn parse_import(&mut self) -> GResult<Import> {
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        let annotations = self.parse_annotations()?;
+        self.next_token();
+
+        let is_static = self.peek_token() == Some(&Token::Keyword(Keywords::Static));
+        if is_static {
+            self.next_token();
+        }
+        let name = self.parse_qualified_name()?;
+
+        let is_star = self.peek_token() == Some(&Token::Period);
+        let mut alias = None;
+        if is_star {
+            self.next_token();
+            self.expect_token(Token::Mul)?;
+        } else if self.peek_token() == Some(&Token::Keyword(Keywords::As)) {
+            self.next_token();
+            alias = Some(self.expect_identifier()?);
+        }
+
+        // a line break after the `*` of a star import is not a token, as `*`
+        // is usually a binary operator
+        if !(is_star && self.at_line_start()) {
+            self.expect_stmt_end()?;
+        }
+        self.finish_node_at(checkpoint, SyntaxKind::ImportDecl);
+
+        Ok(Import {
+            name,
+            alias,
+            is_star,
+            is_static,
+            annotations,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses a dotted name. A `.` which is not followed by an identifier is
+    /// left for the caller, as in `import a.b.*`
+    fn parse_qualified_name(&mut self) -> GResult<QualifiedName> {
+        let checkpoint = self.checkpoint();
+        let mut segments = vec![self.expect_identifier()?];
+        while let (Some(Token::Period), Some(Token::Identifier(..))) =
+            (self.peek_token(), self.peek_nth(1))
+        {
+            self.next_token();
+            segments.push(self.expect_identifier()?);
+        }
+        self.finish_node_at(checkpoint, SyntaxKind::QualifiedName);
+
+        let span = segments[0].span.to(self.prev_span);
+        Ok(QualifiedName { segments, span })
+    }
+
+    /// Whether the upcoming tokens begin a class, interface, enum or trait, possibly
+    /// after some annotations and modifiers. Nothing is consumed
+    fn at_type_declaration(&self) -> bool {
+        let start = (self.pos + self.annotations_len()).min(self.tokens.len());
+        let mut tokens = self.tokens[start..].iter().map(|tok| &tok.token);
+        tokens
+            .find(|token| {
+                !matches!(
+                    token,
+                    Token::Keyword(
+                        Keywords::Public
+                            | Keywords::Private
+                            | Keywords::Protected
+                            | Keywords::Final
+                            | Keywords::Static
+                            | Keywords::Abstract
+                    )
+                ) && !Self::is_contextual(token, &[Keywords::Sealed, Keywords::NonSealed])
+            })
+            .is_some_and(|token| match token {
+                Token::Keyword(Keywords::Class | Keywords::Interface | Keywords::Enum) => true,
+                Token::AtSign => tokens.next() == Some(&Token::Keyword(Keywords::Interface)),
+                // `trait` and `record` are only keywords when a name follows
+                _ => {
+                    Self::is_contextual(token, &[Keywords::Trait, Keywords::Record])
+                        && matches!(tokens.next(), Some(Token::Identifier(..)))
+                }
+            })
+    }
+
+    /// Whether the upcoming tokens look like the start of a method
+    /// declaration, `Type name(`. Nothing is consumed
+    fn at_method_declaration(&mut self) -> bool {
+        let snapshot = self.snapshot();
+        let _ = self.parse_annotations();
+        self.parse_visibility();
+        self.parse_method_modifiers();
+        let is_declaration = self.parse_return_type().is_ok()
+            && matches!(self.peek_token(), Some(Token::Identifier(..)))
+            && self.peek_nth(1) == Some(&Token::ParenOpen);
+        self.restore(snapshot);
+        is_declaration
+    }
+
+    fn parse_type_declaration(&mut self) -> GResult<AstNode> {
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        let doc = self.doc_comment();
+        let annotations = self.parse_annotations()?;
         let visibility = self.parse_visibility();
         let modifiers = self.parse_class_modifiers();
-        match self.lexer.next() {
-            Some(Token::Keyword(Keywords::Class)) => {
-                return Ok(AstNode::Class(
-                    self.parse_class_declaration(visibility, modifiers)?,
+        let kind = match self.peek_token() {
+            Some(Token::Keyword(Keywords::Class)) => ClassKind::Class,
+            Some(Token::Keyword(Keywords::Interface)) => ClassKind::Interface,
+            Some(Token::Keyword(Keywords::Enum)) => ClassKind::Enum,
+            _ if self.at_contextual_keyword(Keywords::Trait) => ClassKind::Trait,
+            _ if self.at_contextual_keyword(Keywords::Record) => ClassKind::Record,
+            Some(Token::AtSign)
+                if self.peek_nth(1) == Some(&Token::Keyword(Keywords::Interface)) =>
+            {
+                self.next_token();
+                ClassKind::Annotation
+            }
+            _ => return Err(self.expected("class declaration")),
+        };
+        self.next_keyword();
+        let name = self.expect_identifier()?;
+
+        let mut class = Class {
+            name,
+            kind,
+            visibility,
+            modifiers,
+            annotations,
+            type_parameters: Vec::new(),
+            super_class: None,
+            methods: Vec::new(),
+            fields: Vec::new(),
+            properties: Vec::new(),
+            interfaces: Vec::new(),
+            permits: Vec::new(),
+            record_components: Vec::new(),
+            enum_constants: Vec::new(),
+            initializers: Vec::new(),
+            doc,
+            span: Span::default(),
+        };
+        if self.peek_token() == Some(&Token::Lt) {
+            class.type_parameters = self.parse_type_parameters()?;
+        }
+        if kind == ClassKind::Record {
+            class.record_components = self.parse_fn_args()?;
+        }
+        self.parse_supertypes(&mut class)?;
+        self.parse_class_body(&mut class)?;
+        class.span = self.span_from(start);
+        self.finish_node_at(checkpoint, SyntaxKind::ClassDecl);
+        Ok(AstNode::Class(class))
+    }
+
+    /// Parses the `extends`, `implements` and `permits` clauses following the
+    /// name of a class
+    fn parse_supertypes(&mut self, class: &mut Class) -> GResult<()> {
+        if self.peek_past_newlines() == Some(&Token::Keyword(Keywords::Extends)) {
+            self.skip_newlines();
+            let checkpoint = self.checkpoint();
+            let keyword = self.peek_span();
+            self.next_token();
+            let types = self.parse_type_list()?;
+            self.finish_node_at(checkpoint, SyntaxKind::ExtendsClause);
+            match class.kind {
+                ClassKind::Interface => class.interfaces.extend(types),
+                ClassKind::Enum | ClassKind::Record => self.diagnostics.push(Diagnostic::error(
+                    ErrorCode::UnexpectedToken,
+                    keyword,
+                    "enums and records cannot extend a class",
+                )),
+                ClassKind::Annotation => self.diagnostics.push(Diagnostic::error(
+                    ErrorCode::UnexpectedToken,
+                    keyword,
+                    "annotation types cannot extend other types",
+                )),
+                ClassKind::Class | ClassKind::Trait => {
+                    let mut types = types.into_iter();
+                    class.super_class = types.next();
+                    if let Some(extra) = types.next() {
+                        self.diagnostics.push(Diagnostic::error(
+                            ErrorCode::UnexpectedToken,
+                            extra.span,
+                            "a class can only extend one class",
+                        ));
+                    }
+                }
+            }
+        }
+
+        if self.peek_past_newlines() == Some(&Token::Keyword(Keywords::Implements)) {
+            self.skip_newlines();
+            let checkpoint = self.checkpoint();
+            let keyword = self.peek_span();
+            self.next_token();
+            let types = self.parse_type_list()?;
+            self.finish_node_at(checkpoint, SyntaxKind::ImplementsClause);
+            if class.kind == ClassKind::Interface {
+                self.diagnostics.push(Diagnostic::error(
+                    ErrorCode::UnexpectedToken,
+                    keyword,
+                    "interfaces use `extends` to inherit other interfaces",
                 ));
             }
-            Some(Token::Keyword(Keywords::Interface)) => todo!("interface declaration"),
-            Some(Token::Keyword(Keywords::Enum)) => todo!("enum declaration"),
-            Some(Token::AtSign) => todo!("attribute declaration"),
-            _ => return Err(GroovyError::new("unknown token at toplevel")),
+            class.interfaces.extend(types);
+        }
+
+        if self
+            .peek_past_newlines()
+            .is_some_and(|token| Self::is_contextual(token, &[Keywords::Permits]))
+        {
+            self.skip_newlines();
+            let checkpoint = self.checkpoint();
+            self.next_keyword();
+            class.permits = self.parse_type_list()?;
+            self.finish_node_at(checkpoint, SyntaxKind::PermitsClause);
+        }
+        Ok(())
+    }
+
+    /// Parses one or more types separated by commas
+    fn parse_type_list(&mut self) -> GResult<Vec<Spanned<Type>>> {
+        let mut types = Vec::new();
+        loop {
+            let start = self.peek_span().start;
+            let ty = self.parse_type()?;
+            types.push(Spanned::new(ty, self.span_from(start)));
+            if self.peek_token() != Some(&Token::Comma) {
+                return Ok(types);
+            }
+            self.next_token();
+        }
+    }
+
+    /// Whether the next token is the identifier `keyword`, which only has a
+    /// special meaning in some positions
+    fn at_contextual_keyword(&self, keyword: Keywords) -> bool {
+        self.peek_token()
+            .is_some_and(|token| Self::is_contextual(token, &[keyword]))
+    }
+
+    /// Whether `token` is an identifier spelling one of `keywords`
+    fn is_contextual(token: &Token, keywords: &[Keywords]) -> bool {
+        match *token {
+            Token::Identifier(ident) => {
+                Keywords::try_from(ident).is_ok_and(|keyword| keywords.contains(&keyword))
+            }
+            _ => false,
         }
     }
 
     fn parse_visibility(&mut self) -> Visibility {
-        if let Some(Token::Keyword(keyword)) = self.lexer.peek() {
+        if let Some(Token::Keyword(keyword)) = self.peek_token() {
             match keyword {
                 Keywords::Public => {
-                    self.lexer.next();
+                    self.next_token();
                     return Visibility::Public;
                 }
                 Keywords::Private => {
-                    self.lexer.next();
+                    self.next_token();
                     return Visibility::Private;
                 }
                 Keywords::Protected => {
-                    self.lexer.next();
+                    self.next_token();
                     return Visibility::Protected;
                 }
                 _ => {}
@@ -78,235 +802,991 @@ impl<'a> GroovyParser<'a> {
 
     fn parse_class_modifiers(&mut self) -> Vec<ClassModifier> {
         let mut modifiers = Vec::new();
-        while let Some(Token::Keyword(keyword)) = self.lexer.peek() {
-            match keyword {
-                Keywords::Final => {
-                    self.lexer.next();
-                    modifiers.push(ClassModifier::Final)
-                }
-                Keywords::Static => {
-                    self.lexer.next();
-                    modifiers.push(ClassModifier::Static)
-                }
+        loop {
+            let modifier = match self.peek_token() {
+                Some(Token::Keyword(Keywords::Final)) => ClassModifier::Final,
+                Some(Token::Keyword(Keywords::Static)) => ClassModifier::Static,
+                Some(Token::Keyword(Keywords::Abstract)) => ClassModifier::Abstract,
+                _ if self.at_contextual_keyword(Keywords::Sealed) => ClassModifier::Sealed,
+                _ if self.at_contextual_keyword(Keywords::NonSealed) => ClassModifier::NonSealed,
                 _ => break,
-            }
+            };
+            self.next_keyword();
+            modifiers.push(modifier);
         }
         modifiers
     }
 
     fn parse_method_modifiers(&mut self) -> Vec<MethodModifier> {
         let mut modifiers = Vec::new();
-        while let Some(Token::Keyword(keyword)) = self.lexer.peek() {
+        while let Some(Token::Keyword(keyword)) = self.peek_token() {
             match keyword {
                 Keywords::Final => {
-                    self.lexer.next();
+                    self.next_token();
                     modifiers.push(MethodModifier::Final)
                 }
                 Keywords::Static => {
-                    self.lexer.next();
+                    self.next_token();
                     modifiers.push(MethodModifier::Static)
                 }
                 Keywords::Abstract => {
-                    self.lexer.next();
+                    self.next_token();
                     modifiers.push(MethodModifier::Abstract)
                 }
                 Keywords::Transient => {
-                    self.lexer.next();
+                    self.next_token();
                     modifiers.push(MethodModifier::Transient)
                 }
                 Keywords::Synchronized => {
-                    self.lexer.next();
+                    self.next_token();
                     modifiers.push(MethodModifier::Synchronized)
                 }
                 Keywords::Volatile => {
-                    self.lexer.next();
+                    self.next_token();
                     modifiers.push(MethodModifier::Volatile)
                 }
+                Keywords::Native => {
+                    self.next_token();
+                    modifiers.push(MethodModifier::Native)
+                }
+                Keywords::Default => {
+                    self.next_token();
+                    modifiers.push(MethodModifier::Default)
+                }
                 _ => break,
             }
         }
         modifiers
     }
 
-    fn parse_class_declaration(
-        &mut self,
-        visibility: Visibility,
-        modifiers: Vec<ClassModifier>,
-    ) -> GResult<Class> {
-        let name = if let Some(Token::Identifier(ident)) = self.lexer.next() {
-            ident
-        } else {
-            return Err(GroovyError::new("class missing name"));
-        };
-
+    /// Parses the members of a class between `{` and `}`, adding them to
+    /// `class`
+    fn parse_class_body(&mut self, class: &mut Class) -> GResult<()> {
+        self.skip_newlines();
         self.expect_token(Token::CurlyBraceOpen)?;
+        let open_brace = self.prev_span;
 
-        let mut methods = Vec::new();
+        if class.kind == ClassKind::Enum {
+            self.parse_enum_constants(class);
+        }
 
         loop {
-            let visibility = self.parse_visibility();
-            let modifiers = self.parse_method_modifiers();
-            let type_name = self.parse_type()?;
-            let ident = self.expect_identifier()?;
-            match self.lexer.peek() {
-                Some(Token::ParenOpen) => methods
-                    .push(self.parse_method_declaration(visibility, modifiers, type_name, ident)?),
-                Some(Token::SingleEqual) => todo!("expr"),
-                _ => todo!(),
-            }
-
-            match self.lexer.peek() {
-                Some(Token::CurlyBraceClose) | None => {
-                    self.lexer.next();
+            match self.peek_token() {
+                Some(Token::CurlyBraceClose) => {
+                    self.next_token();
+                    break;
+                }
+                None => {
+                    self.diagnostics.push(self.unclosed_brace(open_brace));
                     break;
                 }
-                Some(..) => continue,
+                Some(Token::ExprEnd | Token::NewLine) => {
+                    self.next_token();
+                    continue;
+                }
+                Some(..) => {}
             }
-        }
-
-        Ok(Class {
-            name,
-            visibility,
-            modifiers,
-            super_class: None,
-            methods,
-            fields: Vec::new(),
-            properties: Vec::new(),
-            interfaces: Vec::new(),
-            mixin: Vec::new(),
-        })
-    }
-
-    fn parse_method_declaration(
-        &mut self,
-        visibility: Visibility,
-        modifiers: Vec<MethodModifier>,
-        return_type: Type,
-        name: Identifier,
-    ) -> GResult<Method> {
-        let parameters = self.parse_fn_args()?;
-        let body = self.parse_block()?;
 
-        Ok(Method {
-            name,
-            static_constructor: modifiers.contains(&MethodModifier::Static),
-            visibility,
-            modifiers,
-            return_type,
-            parameters,
-            has_default_value: TODO_BOOL,
-            exceptions: Vec::new(),
-            body,
-            is_constructor: TODO_BOOL,
-        })
+            let start_pos = self.pos;
+            let checkpoint = self.checkpoint();
+            if let Err(err) = self.parse_class_member(class) {
+                self.recover(err, start_pos);
+                self.finish_node_at(checkpoint, SyntaxKind::Error);
+            }
+        }
+        Ok(())
     }
 
-    fn parse_fn_args(&mut self) -> GResult<Vec<Parameter>> {
-        self.expect_token(Token::ParenOpen)?;
-
-        let mut params = Vec::new();
-
+    /// Parses the constants at the start of an enum body, up to the `;` or
+    /// line break which separates them from the other members. A malformed
+    /// constant is skipped up to the end of its line, and parsing carries on
+    /// with any constants on the lines after it
+    fn parse_enum_constants(&mut self, class: &mut Class) {
         loop {
-            let param_type = self.parse_type()?;
-            let name = self.expect_identifier()?;
-            if let Some(Token::SquareBraceOpen) = self.lexer.peek() {
-                self.lexer.next();
-                todo!("array params")
-            }
-
-            if let Some(Token::SingleEqual) = self.lexer.peek() {
-                self.lexer.next();
-                todo!("param with default values")
+            self.skip_newlines();
+            let n = self.annotations_len();
+            match (self.peek_nth(n), self.peek_nth(n + 1)) {
+                // a constructor looks like a constant with arguments
+                (Some(&Token::Identifier(name)), Some(Token::ParenOpen))
+                    if name == class.name.node =>
+                {
+                    return
+                }
+                (
+                    Some(Token::Identifier(..)),
+                    Some(
+                        Token::Comma
+                        | Token::ParenOpen
+                        | Token::CurlyBraceOpen
+                        | Token::CurlyBraceClose
+                        | Token::ExprEnd
+                        | Token::NewLine,
+                    )
+                    | None,
+                ) => {}
+                _ => return,
             }
 
-            params.push(Parameter {
-                param_type,
-                name,
-                origin_type: None,
-                dynamically_typed: TODO_BOOL,
-                closure_shared: TODO_BOOL,
-                default_value: None,
-                in_static_context: TODO_BOOL,
-                modifiers: Vec::new(),
-            });
-
-            match self.lexer.peek() {
-                Some(Token::ParenClose) | None => {
-                    self.lexer.next();
-                    break;
+            let start_pos = self.pos;
+            let checkpoint = self.checkpoint();
+            match self.parse_enum_constant() {
+                Ok(constant) => {
+                    self.finish_node_at(checkpoint, SyntaxKind::EnumConstant);
+                    class.enum_constants.push(constant);
+                    match self.peek_token() {
+                        Some(Token::Comma) => {
+                            self.next_token();
+                            continue;
+                        }
+                        Some(Token::ExprEnd) => {
+                            self.next_token();
+                            return;
+                        }
+                        Some(Token::NewLine | Token::CurlyBraceClose) | None => return,
+                        Some(..) => {
+                            let start_pos = self.pos;
+                            let checkpoint = self.checkpoint();
+                            self.recover(self.expected("`,`, `;` or a line break"), start_pos);
+                            self.finish_node_at(checkpoint, SyntaxKind::Error);
+                        }
+                    }
                 }
-                Some(Token::Comma) => continue,
-                Some(..) => {
-                    return Err(GroovyError::new("parameter had something other than comma"))
+                Err(err) => {
+                    self.recover(err, start_pos);
+                    self.finish_node_at(checkpoint, SyntaxKind::Error);
                 }
             }
+            // recovery stops after a `;`, which also ends the constants
+            if self.tokens[self.pos - 1].token == Token::ExprEnd {
+                return;
+            }
         }
-
-        Ok(params)
     }
 
-    fn expect_keyword(&mut self, k: Keywords) -> GResult<()> {
-        if Some(Token::Keyword(k)) == self.lexer.next() {
+    fn parse_enum_constant(&mut self) -> GResult<EnumConstant> {
+        let start = self.peek_span().start;
+        let doc = self.doc_comment();
+        let annotations = self.parse_annotations()?;
+        let name = self.expect_identifier()?;
+        let arguments = if self.peek_token() == Some(&Token::ParenOpen) {
+            self.parse_arguments()?
+        } else {
+            Vec::new()
+        };
+        let body = if self.peek_token() == Some(&Token::CurlyBraceOpen) {
+            let mut body = Class {
+                name,
+                kind: ClassKind::Class,
+                visibility: Visibility::Undefined,
+                modifiers: Vec::new(),
+                annotations: Vec::new(),
+                type_parameters: Vec::new(),
+                super_class: None,
+                methods: Vec::new(),
+                fields: Vec::new(),
+                properties: Vec::new(),
+                interfaces: Vec::new(),
+                permits: Vec::new(),
+                record_components: Vec::new(),
+                enum_constants: Vec::new(),
+                initializers: Vec::new(),
+                doc: None,
+                span: Span::default(),
+            };
+            self.parse_class_body(&mut body)?;
+            body.span = self.span_from(start);
+            Some(Box::new(body))
+        } else {
+            None
+        };
+        Ok(EnumConstant {
+            name,
+            arguments,
+            body,
+            annotations,
+            doc,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses the arguments of a call, `(a, b)`
+    fn parse_arguments(&mut self) -> GResult<Vec<Expr>> {
+        let checkpoint = self.checkpoint();
+        self.expect_token(Token::ParenOpen)?;
+        let mut arguments = Vec::new();
+        if self.peek_token() != Some(&Token::ParenClose) {
+            loop {
+                arguments.push(self.parse_expr()?);
+                if self.peek_token() == Some(&Token::Comma) {
+                    self.next_token();
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect_token(Token::ParenClose)?;
+        self.finish_node_at(checkpoint, SyntaxKind::ArgList);
+        Ok(arguments)
+    }
+
+    /// Parses a method declared at the top level of a script
+    fn parse_script_method(&mut self) -> GResult<Method> {
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        let doc = self.doc_comment();
+        let annotations = self.parse_annotations()?;
+        let visibility = self.parse_visibility();
+        let modifiers = self.parse_method_modifiers();
+        let return_type = self.parse_return_type()?;
+        let name = self.expect_identifier()?;
+        let mut method =
+            self.parse_method_declaration(start, doc, visibility, modifiers, return_type, name)?;
+        method.annotations = annotations;
+        self.finish_node_at(checkpoint, SyntaxKind::MethodDecl);
+        Ok(method)
+    }
+
+    /// Parses a method, constructor, field or initializer block, adding it
+    /// to `class`
+    fn parse_class_member(&mut self, class: &mut Class) -> GResult<()> {
+        let member_start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+
+        let is_static = self.peek_token() == Some(&Token::Keyword(Keywords::Static))
+            && self.peek_nth(1) == Some(&Token::CurlyBraceOpen);
+        if is_static || self.peek_token() == Some(&Token::CurlyBraceOpen) {
+            if is_static {
+                self.next_token();
+            }
+            let body = self.parse_block()?;
+            self.finish_node_at(checkpoint, SyntaxKind::Initializer);
+            class.initializers.push(Initializer {
+                is_static,
+                body,
+                span: self.span_from(member_start),
+            });
+            return Ok(());
+        }
+
+        let doc = self.doc_comment();
+        let annotations = self.parse_annotations()?;
+        let visibility = self.parse_visibility();
+        let modifiers = self.parse_method_modifiers();
+
+        if self.peek_token() == Some(&Token::Identifier(class.name.node)) {
+            match self.peek_nth(1) {
+                // the compact constructor of a record, which takes the
+                // components as its implicit parameters
+                Some(Token::CurlyBraceOpen) if class.kind == ClassKind::Record => {
+                    let name = self.expect_identifier()?;
+                    let body = self.parse_block()?;
+                    self.finish_node_at(checkpoint, SyntaxKind::MethodDecl);
+                    class.methods.push(Method {
+                        name,
+                        visibility,
+                        modifiers,
+                        annotations,
+                        return_type: Type::Void,
+                        parameters: Vec::new(),
+                        has_default_value: false,
+                        exceptions: Vec::new(),
+                        static_constructor: false,
+                        body: Some(body),
+                        is_constructor: true,
+                        annotation_default: None,
+                        doc,
+                        span: self.span_from(member_start),
+                    });
+                    return Ok(());
+                }
+                Some(Token::ParenOpen) => {
+                    let name = self.expect_identifier()?;
+                    let mut constructor = self.parse_method_declaration(
+                        member_start,
+                        doc,
+                        visibility,
+                        modifiers,
+                        Type::Void,
+                        name,
+                    )?;
+                    constructor.is_constructor = true;
+                    constructor.annotations = annotations;
+                    self.finish_node_at(checkpoint, SyntaxKind::MethodDecl);
+                    class.methods.push(constructor);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        // a field may leave out its type if it has a modifier, as in
+        // `static count = 0`
+        let untyped = (visibility != Visibility::Undefined || !modifiers.is_empty())
+            && matches!(self.peek_token(), Some(Token::Identifier(..)))
+            && matches!(
+                self.peek_nth(1),
+                Some(
+                    Token::SingleEqual
+                        | Token::Comma
+                        | Token::ExprEnd
+                        | Token::NewLine
+                        | Token::CurlyBraceClose
+                ) | None
+            );
+        let type_name = if untyped {
+            Type::Placeholder
+        } else {
+            self.parse_return_type()?
+        };
+        // the declarator of a field starts at its name
+        let mut declarator = self.checkpoint();
+        let name = self.expect_identifier()?;
+        if self.peek_token() == Some(&Token::ParenOpen) {
+            let mut method = self.parse_method_declaration(
+                member_start,
+                doc,
+                visibility,
+                modifiers,
+                type_name,
+                name,
+            )?;
+            method.annotations = annotations;
+            self.finish_node_at(checkpoint, SyntaxKind::MethodDecl);
+            class.methods.push(method);
+            return Ok(());
+        }
+
+        // fields declared without a visibility are properties, except in
+        // interfaces, where they are constants
+        let is_property = visibility == Visibility::Undefined && class.kind != ClassKind::Interface;
+        let mut name = name;
+        loop {
+            let initial_value = if self.peek_token() == Some(&Token::SingleEqual) {
+                self.next_token();
+                Some(self.parse_expr()?)
+            } else {
+                None
+            };
+            self.finish_node_at(declarator, SyntaxKind::Declarator);
+            let field = Field {
+                name,
+                visibility,
+                modifiers: modifiers.clone(),
+                annotations: annotations.clone(),
+                field_type: type_name.clone(),
+                initial_value,
+                doc: doc.clone(),
+                span: self.span_from(member_start),
+            };
+            if is_property {
+                class.properties.push(Property { field });
+            } else {
+                class.fields.push(field);
+            }
+
+            if self.peek_token() != Some(&Token::Comma) {
+                break;
+            }
+            self.next_token();
+            declarator = self.checkpoint();
+            name = self.expect_identifier()?;
+        }
+        self.expect_stmt_end()?;
+        self.finish_node_at(checkpoint, SyntaxKind::FieldDecl);
+        Ok(())
+    }
+
+    fn parse_method_declaration(
+        &mut self,
+        start: usize,
+        doc: Option<String>,
+        visibility: Visibility,
+        modifiers: Vec<MethodModifier>,
+        return_type: Type,
+        name: Spanned<Identifier>,
+    ) -> GResult<Method> {
+        let parameters = self.parse_fn_args()?;
+        let mut exceptions = Vec::new();
+        if self.peek_past_newlines() == Some(&Token::Keyword(Keywords::Throws)) {
+            self.skip_newlines();
+            let checkpoint = self.checkpoint();
+            self.next_token();
+            exceptions = self.parse_type_list()?;
+            self.finish_node_at(checkpoint, SyntaxKind::ThrowsClause);
+        }
+        // the members of an annotation type may have a default value, as in
+        // `String value() default ''`
+        let mut annotation_default = None;
+        if self.peek_token() == Some(&Token::Keyword(Keywords::Default)) {
+            let checkpoint = self.checkpoint();
+            self.next_token();
+            annotation_default = Some(self.parse_annotation_value()?);
+            self.finish_node_at(checkpoint, SyntaxKind::AnnotationDefault);
+        }
+        let body = if self.at_block() {
+            Some(self.parse_block()?)
+        } else {
+            self.expect_stmt_end()?;
+            None
+        };
+
+        Ok(Method {
+            name,
+            static_constructor: modifiers.contains(&MethodModifier::Static),
+            visibility,
+            modifiers,
+            annotations: Vec::new(),
+            return_type,
+            has_default_value: parameters.iter().any(|param| param.default_value.is_some()),
+            parameters,
+            exceptions,
+            body,
+            is_constructor: false,
+            annotation_default,
+            doc,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_fn_args(&mut self) -> GResult<Vec<Parameter>> {
+        let list_checkpoint = self.checkpoint();
+        self.expect_token(Token::ParenOpen)?;
+
+        let mut params = Vec::new();
+        if self.peek_token() == Some(&Token::ParenClose) {
+            self.next_token();
+            self.finish_node_at(list_checkpoint, SyntaxKind::ParamList);
+            return Ok(params);
+        }
+
+        loop {
+            let start = self.peek_span().start;
+            let checkpoint = self.checkpoint();
+            // `final` and annotations may appear in any order, `final @Ann String a`
+            let mut annotations = Vec::new();
+            let mut modifiers = Vec::new();
+            loop {
+                match self.peek_token() {
+                    Some(Token::Keyword(Keywords::Final)) => {
+                        self.next_token();
+                        modifiers.push(MethodModifier::Final);
+                    }
+                    Some(Token::AtSign) => annotations.push(self.parse_annotation()?),
+                    _ => break,
+                }
+            }
+
+            // the type may be left out entirely, as in `def f(a, b = 1)`
+            let untyped = matches!(self.peek_token(), Some(Token::Identifier(..)))
+                && matches!(
+                    self.peek_nth(1),
+                    Some(Token::Comma | Token::ParenClose | Token::SingleEqual)
+                );
+            let mut param_type = if untyped {
+                Type::Placeholder
+            } else {
+                self.parse_return_type()?
+            };
+            let ellipsis = self.peek_span();
+            let is_varargs = self.peek_token() == Some(&Token::Ellipsis);
+            if is_varargs {
+                self.next_token();
+                param_type = Type::Array(Box::new(param_type));
+            }
+            let name = self.expect_identifier()?;
+            // the C-style `String args[]`
+            while let (Some(Token::SquareBraceOpen), Some(Token::SquareBraceClose)) =
+                (self.peek_token(), self.peek_nth(1))
+            {
+                self.next_token();
+                self.next_token();
+                param_type = Type::Array(Box::new(param_type));
+            }
+
+            let default_value = if self.peek_token() == Some(&Token::SingleEqual) {
+                self.next_token();
+                Some(self.parse_expr()?)
+            } else {
+                None
+            };
+
+            params.push(Parameter {
+                dynamically_typed: param_type == Type::Placeholder,
+                param_type,
+                name,
+                origin_type: None,
+                closure_shared: TODO_BOOL,
+                default_value,
+                in_static_context: TODO_BOOL,
+                modifiers,
+                annotations,
+                is_varargs,
+                span: self.span_from(start),
+            });
+            self.finish_node_at(checkpoint, SyntaxKind::Param);
+
+            match self.peek_token() {
+                Some(Token::ParenClose) => {
+                    self.next_token();
+                    break;
+                }
+                Some(Token::Comma) => {
+                    if is_varargs {
+                        self.diagnostics.push(Diagnostic::error(
+                            ErrorCode::UnexpectedToken,
+                            ellipsis,
+                            "only the last parameter can be varargs",
+                        ));
+                    }
+                    self.next_token();
+                    continue;
+                }
+                Some(..) | None => return Err(self.expected("`,` or `)`")),
+            }
+        }
+        self.finish_node_at(list_checkpoint, SyntaxKind::ParamList);
+
+        Ok(params)
+    }
+
+    /// Parses the annotations preceding a declaration, each of which may be
+    /// on a line of its own
+    fn parse_annotations(&mut self) -> GResult<Vec<Annotation>> {
+        let mut annotations = Vec::new();
+        while self.peek_token() == Some(&Token::AtSign)
+            && self.peek_nth(1) != Some(&Token::Keyword(Keywords::Interface))
+        {
+            annotations.push(self.parse_annotation()?);
+            while self.peek_token() == Some(&Token::NewLine) {
+                self.next_token();
+            }
+        }
+        Ok(annotations)
+    }
+
+    /// Parses a single annotation, `@Name` or `@Name(value, key = value)`
+    fn parse_annotation(&mut self) -> GResult<Annotation> {
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        self.expect_token(Token::AtSign)?;
+        let name = self.parse_qualified_name()?;
+
+        let mut elements = Vec::new();
+        if self.peek_token() == Some(&Token::ParenOpen) {
+            self.next_token();
+            while self.peek_token() != Some(&Token::ParenClose) {
+                let start = self.peek_span().start;
+                let element = self.checkpoint();
+                let name = match (self.peek_token(), self.peek_nth(1)) {
+                    (Some(Token::Identifier(..)), Some(Token::SingleEqual)) => {
+                        let name = self.expect_identifier()?;
+                        self.next_token();
+                        Some(name)
+                    }
+                    _ => None,
+                };
+                let value = self.parse_annotation_value()?;
+                self.finish_node_at(element, SyntaxKind::AnnotationElement);
+                elements.push(AnnotationElement {
+                    name,
+                    value,
+                    span: self.span_from(start),
+                });
+                if self.peek_token() != Some(&Token::Comma) {
+                    break;
+                }
+                self.next_token();
+            }
+            self.expect_token(Token::ParenClose)?;
+        }
+
+        self.finish_node_at(checkpoint, SyntaxKind::Annotation);
+        Ok(Annotation {
+            name,
+            elements,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses the value of an annotation element, which is a constant
+    /// expression, another annotation, a list of values or a closure
+    fn parse_annotation_value(&mut self) -> GResult<AnnotationValue> {
+        Ok(match self.peek_token() {
+            Some(Token::AtSign) => AnnotationValue::Annotation(Box::new(self.parse_annotation()?)),
+            Some(Token::SquareBraceOpen) => {
+                let checkpoint = self.checkpoint();
+                self.next_token();
+                let mut values = Vec::new();
+                while self.peek_token() != Some(&Token::SquareBraceClose) {
+                    values.push(self.parse_annotation_value()?);
+                    if self.peek_token() != Some(&Token::Comma) {
+                        break;
+                    }
+                    self.next_token();
+                }
+                self.expect_token(Token::SquareBraceClose)?;
+                self.finish_node_at(checkpoint, SyntaxKind::AnnotationArray);
+                AnnotationValue::Array(values)
+            }
+            Some(Token::CurlyBraceOpen) => AnnotationValue::Closure(Box::new(self.parse_block()?)),
+            _ => AnnotationValue::Expr(self.parse_expr()?),
+        })
+    }
+
+    /// The number of tokens taken up by the annotations at the current
+    /// position, including any line breaks following them. Nothing is
+    /// consumed
+    fn annotations_len(&self) -> usize {
+        let mut n = 0;
+        while self.peek_nth(n) == Some(&Token::AtSign)
+            && matches!(self.peek_nth(n + 1), Some(Token::Identifier(..)))
+        {
+            n += 2;
+            while let (Some(Token::Period), Some(Token::Identifier(..))) =
+                (self.peek_nth(n), self.peek_nth(n + 1))
+            {
+                n += 2;
+            }
+            if self.peek_nth(n) == Some(&Token::ParenOpen) {
+                let mut depth = 0_usize;
+                loop {
+                    match self.peek_nth(n) {
+                        Some(Token::ParenOpen) => depth += 1,
+                        Some(Token::ParenClose) => depth -= 1,
+                        Some(..) => {}
+                        None => return n,
+                    }
+                    n += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            while self.peek_nth(n) == Some(&Token::NewLine) {
+                n += 1;
+            }
+        }
+        n
+    }
+
+    fn expect_keyword(&mut self, k: Keywords) -> GResult<()> {
+        if self.peek_token() == Some(&Token::Keyword(k)) {
+            self.next_token();
             return Ok(());
         }
-        Err(GroovyError::new("expected keyword"))
+        Err(self.expected(&format!("keyword `{}`", k.as_str())))
     }
 
     fn expect_token(&mut self, tok: Token) -> GResult<()> {
-        if Some(tok) != self.lexer.next() {
-            return Err(GroovyError::new("expected token"));
+        if self.peek_token() != Some(&tok) {
+            return Err(self.expected(&tok.to_string()));
         }
+        self.next_token();
         Ok(())
     }
 
-    fn expect_identifier(&mut self) -> GResult<Identifier> {
-        if let Some(Token::Identifier(ident)) = self.lexer.next() {
-            return Ok(ident);
+    fn expect_identifier(&mut self) -> GResult<Spanned<Identifier>> {
+        if let Some(&Token::Identifier(ident)) = self.peek_token() {
+            self.next_token();
+            return Ok(Spanned::new(ident, self.prev_span));
         }
-        return Err(GroovyError::new("expected identifier"));
+        Err(self.expected("identifier"))
     }
 }
 
 impl GroovyParser<'_> {
     fn parse_type(&mut self) -> GResult<Type> {
-        let initial_type = match self.lexer.peek() {
-            Some(Token::Keyword(Keywords::Void)) => Type::Void,
-            Some(Token::Keyword(Keywords::Int)) => Type::Int,
-            Some(Token::Keyword(Keywords::Double)) => Type::Double,
-            Some(Token::Keyword(Keywords::Float)) => Type::Float,
-            Some(Token::Keyword(Keywords::Short)) => Type::Short,
-            Some(Token::Keyword(Keywords::Char)) => Type::Char,
-            Some(Token::Keyword(Keywords::Boolean)) => Type::Boolean,
-            Some(Token::Keyword(Keywords::Byte)) => Type::Byte,
-            Some(Token::Keyword(Keywords::Long)) => Type::Long,
-            Some(Token::Identifier(ident)) => Type::Class(*ident),
-            _ => return Err(GroovyError::new("expected type")),
-        };
-        self.lexer.next();
-        if let Some(Token::SquareBraceOpen) = self.lexer.peek() {
-            self.lexer.next();
-            todo!("array type")
-        }
-        Ok(initial_type)
+        let checkpoint = self.checkpoint();
+        let mut ty = match self.peek_token() {
+            Some(Token::Identifier(..)) => self.parse_class_type()?,
+            Some(&Token::Keyword(keyword)) => {
+                let primitive = match keyword {
+                    Keywords::Void => Type::Void,
+                    Keywords::Int => Type::Int,
+                    Keywords::Double => Type::Double,
+                    Keywords::Float => Type::Float,
+                    Keywords::Short => Type::Short,
+                    Keywords::Char => Type::Char,
+                    Keywords::Boolean => Type::Boolean,
+                    Keywords::Byte => Type::Byte,
+                    Keywords::Long => Type::Long,
+                    _ => return Err(self.expected("type")),
+                };
+                self.next_token();
+                primitive
+            }
+            _ => return Err(self.expected("type")),
+        };
+        while self.peek_token() == Some(&Token::SquareBraceOpen)
+            && self.peek_nth(1) == Some(&Token::SquareBraceClose)
+        {
+            self.next_token();
+            self.next_token();
+            ty = Type::Array(Box::new(ty));
+        }
+        self.finish_node_at(checkpoint, SyntaxKind::TypeRef);
+        Ok(ty)
+    }
+
+    /// Parses a possibly qualified class name and its type arguments,
+    /// `java.util.Map<String, List<?>>`
+    fn parse_class_type(&mut self) -> GResult<Type> {
+        let mut qualifier = Vec::new();
+        let mut name = self.expect_identifier()?.node;
+        while self.peek_token() == Some(&Token::Period)
+            && matches!(self.peek_nth(1), Some(Token::Identifier(..)))
+        {
+            self.next_token();
+            qualifier.push(name);
+            name = self.expect_identifier()?.node;
+        }
+        let arguments = match self.peek_token() {
+            Some(Token::Lt) => Some(self.parse_type_arguments()?),
+            Some(Token::Diamond) => {
+                let checkpoint = self.checkpoint();
+                self.next_token();
+                self.finish_node_at(checkpoint, SyntaxKind::TypeArgList);
+                Some(Vec::new())
+            }
+            _ => None,
+        };
+        Ok(Type::Class {
+            qualifier,
+            name,
+            arguments,
+        })
+    }
+
+    /// Parses the type arguments of a generic type, `<String, ? extends T>`
+    fn parse_type_arguments(&mut self) -> GResult<Vec<Type>> {
+        let list_checkpoint = self.checkpoint();
+        self.expect_token(Token::Lt)?;
+        let mut arguments = Vec::new();
+        loop {
+            if self.peek_token() == Some(&Token::QuestionMark) {
+                let checkpoint = self.checkpoint();
+                self.next_token();
+                let bound = match self.peek_token() {
+                    Some(Token::Keyword(Keywords::Extends)) => {
+                        self.next_token();
+                        Some(WildcardBound::Extends(Box::new(self.parse_type()?)))
+                    }
+                    Some(Token::Keyword(Keywords::Super)) => {
+                        self.next_token();
+                        Some(WildcardBound::Super(Box::new(self.parse_type()?)))
+                    }
+                    _ => None,
+                };
+                self.finish_node_at(checkpoint, SyntaxKind::TypeRef);
+                arguments.push(Type::Wildcard(bound));
+            } else {
+                arguments.push(self.parse_type()?);
+            }
+            if self.peek_token() != Some(&Token::Comma) {
+                break;
+            }
+            self.next_token();
+        }
+        self.expect_closing_angle()?;
+        self.finish_node_at(list_checkpoint, SyntaxKind::TypeArgList);
+        Ok(arguments)
+    }
+
+    /// Parses the type parameters of a generic declaration,
+    /// `<K, V extends Number & Comparable<V>>`
+    fn parse_type_parameters(&mut self) -> GResult<Vec<TypeParameter>> {
+        let list_checkpoint = self.checkpoint();
+        self.expect_token(Token::Lt)?;
+        let mut params = Vec::new();
+        loop {
+            let checkpoint = self.checkpoint();
+            let name = self.expect_identifier()?;
+            let mut bounds = Vec::new();
+            if self.peek_token() == Some(&Token::Keyword(Keywords::Extends)) {
+                self.next_token();
+                loop {
+                    let start = self.peek_span().start;
+                    let bound = self.parse_type()?;
+                    bounds.push(Spanned::new(bound, self.span_from(start)));
+                    if self.peek_token() != Some(&Token::BitwiseAnd) {
+                        break;
+                    }
+                    self.next_token();
+                }
+            }
+            self.finish_node_at(checkpoint, SyntaxKind::TypeParam);
+            params.push(TypeParameter {
+                span: self.span_from(name.span.start),
+                name,
+                bounds,
+            });
+            if self.peek_token() != Some(&Token::Comma) {
+                break;
+            }
+            self.next_token();
+        }
+        self.expect_closing_angle()?;
+        self.finish_node_at(list_checkpoint, SyntaxKind::TypeParamList);
+        Ok(params)
+    }
+
+    /// Consumes the `>` closing a list of type arguments or parameters. The
+    /// lexer cannot tell nested lists from shifts, so a `>>` or `>>>` is split
+    /// and only its first `>` consumed
+    fn expect_closing_angle(&mut self) -> GResult<()> {
+        let rest = match self.peek_token() {
+            Some(Token::Gt) => None,
+            Some(Token::Shr) => Some(Token::Gt),
+            Some(Token::UnsignedShr) => Some(Token::Shr),
+            _ => return Err(self.expected("`>`")),
+        };
+        if let Some(rest) = rest {
+            let original = self.tokens[self.pos].clone();
+            let span = original.span;
+            self.tokens[self.pos] = SpannedToken {
+                token: Token::Gt,
+                span: Span::new(span.start, span.start + 1),
+            };
+            self.tokens.insert(
+                self.pos + 1,
+                SpannedToken {
+                    token: rest,
+                    span: Span::new(span.start + 1, span.end),
+                },
+            );
+            self.splits.push((self.pos, original));
+        }
+        self.next_token();
+        Ok(())
+    }
+
+    /// Parses the type of a method, which may be left dynamic with `def`
+    fn parse_return_type(&mut self) -> GResult<Type> {
+        if self.peek_token() == Some(&Token::Keyword(Keywords::Def)) {
+            let checkpoint = self.checkpoint();
+            self.next_token();
+            self.finish_node_at(checkpoint, SyntaxKind::TypeRef);
+            return Ok(Type::Placeholder);
+        }
+        self.parse_type()
+    }
+
+    /// Whether the upcoming tokens look like the start of a local variable
+    /// declaration, `Type name`. Nothing is consumed
+    fn at_variable_declaration(&mut self) -> bool {
+        let snapshot = self.snapshot();
+        let is_declaration =
+            self.parse_type().is_ok() && matches!(self.peek_token(), Some(Token::Identifier(..)));
+        self.restore(snapshot);
+        is_declaration
     }
 }
 
 impl GroovyParser<'_> {
     fn parse_expr(&mut self) -> GResult<Expr> {
-        self.parse_bin_op(0)
+        let checkpoint = self.checkpoint();
+        let target = self.parse_bin_op(0)?;
+        if let Some(Token::QuestionMark | Token::Elvis) = self.peek_token() {
+            return self.skip_conditional(checkpoint, target);
+        }
+        let op = match self.peek_token() {
+            Some(Token::SingleEqual) => AssignmentOperator::Assign,
+            Some(Token::AddAssign) => AssignmentOperator::AddAssign,
+            Some(Token::SubAssign) => AssignmentOperator::SubAssign,
+            Some(Token::MulAssign) => AssignmentOperator::MulAssign,
+            Some(Token::DivAssign) => AssignmentOperator::DivAssign,
+            Some(Token::RemAssign) => AssignmentOperator::RemAssign,
+            Some(Token::PowAssign) => AssignmentOperator::PowAssign,
+            Some(Token::BitwiseAndAssign) => AssignmentOperator::BitwiseAndAssign,
+            Some(Token::BitwiseOrAssign) => AssignmentOperator::BitwiseOrAssign,
+            Some(Token::XorAssign) => AssignmentOperator::XorAssign,
+            Some(Token::ShlAssign) => AssignmentOperator::ShlAssign,
+            Some(Token::ShrAssign) => AssignmentOperator::ShrAssign,
+            Some(Token::UnsignedShrAssign) => AssignmentOperator::UnsignedShrAssign,
+            Some(Token::BitwiseNotAssign) => AssignmentOperator::BitwiseNotAssign,
+            Some(Token::ElvisAssignment) => AssignmentOperator::ElvisAssignment,
+            _ => return Ok(target),
+        };
+        self.next_token();
+
+        // assignments are right associative, so `a = b = 1` assigns to `b`
+        // first
+        let value = self.parse_expr()?;
+        self.finish_node_at(checkpoint, SyntaxKind::AssignExpr);
+        let span = target.span.to(value.span);
+        Ok(Expr::new(
+            ExprKind::Assignment {
+                target: Box::new(target),
+                op,
+                value: Box::new(value),
+            },
+            span,
+        ))
+    }
+
+    /// Skips the rest of `a ? b : c` or `a ?: b`, which are not supported
+    /// yet, leaving an error node in place of the whole expression
+    fn skip_conditional(&mut self, checkpoint: usize, condition: Expr) -> GResult<Expr> {
+        if self.peek_token() == Some(&Token::Elvis) {
+            self.diagnostics.push(self.unsupported("elvis operators"));
+            self.next_token();
+        } else {
+            self.diagnostics
+                .push(self.unsupported("ternary expressions"));
+            self.next_token();
+            self.parse_expr()?;
+            self.expect_token(Token::Colon)?;
+        }
+        self.parse_expr()?;
+        self.finish_node_at(checkpoint, SyntaxKind::Error);
+        Ok(Expr::new(
+            ExprKind::Error,
+            condition.span.to(self.prev_span),
+        ))
     }
 
     fn parse_bin_op(&mut self, min_precendence: u8) -> GResult<Expr> {
+        let checkpoint = self.checkpoint();
         let mut lhs = self.parse_expr_atom()?;
 
         loop {
-            let op = match self.lexer.peek() {
+            let op = match self.peek_token() {
                 Some(Token::Add) => BinaryOperator::Add,
                 Some(Token::Sub) => BinaryOperator::Sub,
                 Some(Token::Mul) => BinaryOperator::Mul,
                 Some(Token::Div) => BinaryOperator::Div,
+                Some(Token::Rem) => BinaryOperator::Rem,
+                Some(Token::Pow) => BinaryOperator::Pow,
+                Some(Token::Eq) => BinaryOperator::Equal,
+                Some(Token::Ne) => BinaryOperator::NotEqual,
+                Some(Token::Lt) => BinaryOperator::LessThan,
+                Some(Token::Gt) => BinaryOperator::GreaterThan,
+                Some(Token::Le) => BinaryOperator::LessThanOrEqual,
+                Some(Token::Ge) => BinaryOperator::GreaterThanOrEqual,
+                Some(Token::Identical) => BinaryOperator::Identical,
+                Some(Token::NotIdentical) => BinaryOperator::NotIdentical,
+                Some(Token::Spaceship) => BinaryOperator::Diamond,
+                Some(Token::LogicalAnd) => BinaryOperator::LogicalAnd,
+                Some(Token::LogicalOr) => BinaryOperator::LogicalOr,
+                Some(Token::BitwiseAnd) => BinaryOperator::BitwiseAnd,
+                Some(Token::BitwiseOr) => BinaryOperator::BitwiseOr,
+                Some(Token::Xor) => BinaryOperator::Xor,
+                Some(Token::Shl) => BinaryOperator::Shl,
+                Some(Token::Shr) => BinaryOperator::Shr,
+                Some(Token::UnsignedShr) => BinaryOperator::UnsignedShr,
+                Some(Token::Find) => BinaryOperator::Find,
+                Some(Token::Match) => BinaryOperator::Match,
+                Some(Token::Keyword(Keywords::In)) => BinaryOperator::In,
+                Some(Token::NotIn) => BinaryOperator::NotIn,
+                Some(Token::Keyword(Keywords::InstanceOf)) => BinaryOperator::InstanceOf,
+                Some(Token::NotInstanceOf) => BinaryOperator::NotInstanceOf,
+                Some(Token::Keyword(Keywords::As)) => BinaryOperator::As,
+                Some(Token::InclusiveRange) => BinaryOperator::InclusiveRange,
+                Some(Token::ExclusiveRange) => BinaryOperator::ExclusiveRange,
+                Some(Token::ExclusiveLeftRange) => BinaryOperator::ExclusiveLeftRange,
+                Some(Token::ExclusiveBothRange) => BinaryOperator::ExclusiveBothRange,
                 Some(Token::ExprEnd) => {
-                    self.lexer.next();
+                    self.next_token();
                     break;
                 }
                 Some(..) | None => break,
@@ -317,118 +1797,872 @@ impl GroovyParser<'_> {
                 break;
             }
 
-            self.lexer.next();
+            // the right-hand side of these is a type, and they are skipped
+            // as they are not supported yet
+            if let BinaryOperator::InstanceOf | BinaryOperator::NotInstanceOf | BinaryOperator::As =
+                op
+            {
+                let what = if op == BinaryOperator::As {
+                    "`as` casts"
+                } else {
+                    "`instanceof` checks"
+                };
+                self.diagnostics.push(self.unsupported(what));
+                self.next_token();
+                self.parse_type()?;
+                self.finish_node_at(checkpoint, SyntaxKind::Error);
+                lhs = Expr::new(ExprKind::Error, lhs.span.to(self.prev_span));
+                continue;
+            }
+
+            self.next_token();
 
             let rhs = self.parse_bin_op(rhs_precedence)?;
+            self.finish_node_at(checkpoint, SyntaxKind::BinExpr);
 
-            lhs = Expr::BinaryOp {
-                left: Box::new(lhs),
-                op,
-                right: Box::new(rhs),
-                is_safe: TODO_BOOL,
-            };
+            let span = lhs.span.to(rhs.span);
+            lhs = Expr::new(
+                ExprKind::BinaryOp {
+                    left: Box::new(lhs),
+                    op,
+                    right: Box::new(rhs),
+                    is_safe: TODO_BOOL,
+                },
+                span,
+            );
         }
 
         Ok(lhs)
     }
 
     fn parse_expr_atom(&mut self) -> GResult<Expr> {
-        Ok(match self.lexer.next() {
-            Some(Token::Literal(Literal::Number(number))) => {
-                Expr::Constant(ConstExpr::Number(number.to_owned()))
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        let kind = match self.peek_token() {
+            Some(Token::Literal(Literal::Integer(n))) => {
+                ExprKind::Constant(ConstExpr::Integer(n.clone()))
+            }
+            Some(Token::Literal(Literal::Decimal(n))) => {
+                ExprKind::Constant(ConstExpr::Decimal(n.clone()))
             }
-            Some(Token::Literal(Literal::Null)) => Expr::Constant(ConstExpr::Null),
-            Some(Token::Literal(Literal::True)) => Expr::Constant(ConstExpr::True),
-            Some(Token::Literal(Literal::False)) => Expr::Constant(ConstExpr::False),
+            Some(Token::Literal(Literal::Null)) => ExprKind::Constant(ConstExpr::Null),
+            Some(Token::Literal(Literal::True)) => ExprKind::Constant(ConstExpr::True),
+            Some(Token::Literal(Literal::False)) => ExprKind::Constant(ConstExpr::False),
             Some(Token::Literal(Literal::String(string))) => {
-                Expr::Constant(ConstExpr::String(string.to_owned()))
+                ExprKind::Constant(ConstExpr::String(string.clone()))
             }
-            tok => todo!("unexpected token in expr: {:?}", tok),
-        })
-    }
-}
-
-impl GroovyParser<'_> {
-    fn parse_stmt(&mut self) -> GResult<Stmt> {
-        match self.lexer.peek() {
-            Some(Token::CurlyBraceOpen) => self.parse_block(),
-            Some(Token::Keyword(Keywords::Assert)) => {
-                let bool_expr = self.parse_expr()?;
-                let message = if let Some(Token::Comma) = self.lexer.peek() {
-                    self.lexer.next();
-                    Some(self.parse_expr()?)
-                } else {
-                    None
+            Some(&Token::Identifier(name)) => ExprKind::Variable { name },
+            Some(
+                Token::PlusPlus
+                | Token::MinusMinus
+                | Token::Add
+                | Token::Sub
+                | Token::LogicalNot
+                | Token::Tilde,
+            ) => {
+                let op = match self.next_token() {
+                    Some(Token::PlusPlus) => UnaryOperator::Increment,
+                    Some(Token::MinusMinus) => UnaryOperator::Decrement,
+                    Some(Token::Add) => UnaryOperator::Plus,
+                    Some(Token::Sub) => UnaryOperator::Minus,
+                    Some(Token::LogicalNot) => UnaryOperator::LogicalNot,
+                    _ => UnaryOperator::BitwiseNot,
                 };
-                Ok(Stmt::Assert { bool_expr, message })
+                let expr = self.parse_expr_atom()?;
+                self.finish_node_at(checkpoint, SyntaxKind::PrefixExpr);
+                return Ok(Expr::new(
+                    ExprKind::Prefix {
+                        op,
+                        expr: Box::new(expr),
+                    },
+                    self.span_from(start),
+                ));
             }
-            Some(Token::Keyword(Keywords::Break)) => todo!("break stmt"),
-            Some(Token::Keyword(Keywords::Class)) => todo!("class stmt"),
-            Some(Token::Keyword(Keywords::Continue)) => todo!("continue stmt"),
-            Some(Token::Keyword(Keywords::Return)) => {
+            Some(Token::GStringStart) => {
+                let gstring = self.parse_gstring();
+                return self.parse_postfix(checkpoint, gstring);
+            }
+            Some(Token::ParenOpen) => {
+                self.next_token();
                 let expr = self.parse_expr()?;
-                Ok(Stmt::Return { expr })
-            }
-            Some(Token::Keyword(Keywords::Throw)) => todo!("throw stmt"),
-            Some(Token::Keyword(Keywords::Try)) => todo!("try stmt"),
-            Some(Token::Keyword(Keywords::For)) => todo!("for stmt"),
-            Some(Token::Keyword(Keywords::Do)) => todo!("do .. while stmt"),
-            Some(Token::Keyword(Keywords::While)) => todo!("while stmt"),
-            Some(Token::Keyword(Keywords::Switch)) => todo!("switch stmt"),
-            Some(Token::Keyword(Keywords::Synchronized)) => todo!("synchronized stmt"),
-            Some(Token::Keyword(Keywords::If)) => todo!("if stmt"),
-            Some(Token::Keyword(Keywords::Def | Keywords::Var)) => {
-                self.lexer.next();
-                let name = self.expect_identifier()?;
-                let value = if let Some(Token::SingleEqual) = self.lexer.peek() {
-                    self.lexer.next();
-                    self.parse_expr()?
-                } else {
-                    Expr::Constant(ConstExpr::Null)
-                };
-                Ok(Stmt::VariableDeclaration(Variable {
-                    name,
-                    value,
-                    is_closure_shared_variable: TODO_BOOL,
-                    is_dynamically_typed: true,
-                    in_static_context: TODO_BOOL,
-                    type_name: Type::Placeholder,
-                    modifiers: Vec::new(),
-                }))
+                self.expect_token(Token::ParenClose)?;
+                let expr = Expr::new(expr.kind, self.span_from(start));
+                self.finish_node_at(checkpoint, SyntaxKind::ParenExpr);
+                return self.parse_postfix(checkpoint, expr);
             }
-            _ => {
-                if let Ok(type_name) = self.parse_type() {
-                    self.parse_variable_declaration(type_name)
-                } else if let Ok(expr) = self.parse_expr() {
-                    Ok(Stmt::Expression(expr))
-                } else {
-                    return Err(GroovyError::new("expected stmt"));
-                }
+            // the expression is missing entirely, as in `int x = }`. rather
+            // than discarding the surrounding statement, we record the error
+            // and leave a hole in the tree
+            Some(Token::ExprEnd | Token::NewLine | Token::CurlyBraceClose | Token::ParenClose)
+            | None => {
+                return Ok(self.missing_expr());
             }
-        }
+            Some(Token::CurlyBraceOpen) => {
+                self.skip_unsupported_expr("closures");
+                return self.parse_unsupported_postfix(checkpoint, start);
+            }
+            Some(Token::SquareBraceOpen) => {
+                self.skip_unsupported_expr("lists and maps");
+                return self.parse_unsupported_postfix(checkpoint, start);
+            }
+            Some(&Token::Keyword(keyword @ (Keywords::New | Keywords::This | Keywords::Super))) => {
+                self.skip_unsupported_expr(&format!("`{}` expressions", keyword.as_str()));
+                return self.parse_unsupported_postfix(checkpoint, start);
+            }
+            _ => return Err(self.expected("expression")),
+        };
+        self.next_token();
+        let node_kind = match kind {
+            ExprKind::Variable { .. } => SyntaxKind::NameRef,
+            _ => SyntaxKind::LiteralExpr,
+        };
+        self.finish_node_at(checkpoint, node_kind);
+        let expr = Expr::new(kind, self.prev_span);
+        self.parse_postfix(checkpoint, expr)
     }
 
-    fn parse_block(&mut self) -> GResult<Stmt> {
-        self.expect_token(Token::CurlyBraceOpen)?;
+    /// Records that the member access or subscript at the next token is not
+    /// supported, and skips it along with the name, arguments and closure of
+    /// any method it calls, as in `a?.b(c) { }`
+    fn skip_unsupported_member(&mut self, what: &str) {
+        self.diagnostics.push(self.unsupported(what));
+        if self.at_group_start() {
+            self.skip_group();
+            return;
+        }
+        self.next_token();
+        if let Some(Token::Identifier(..)) = self.peek_token() {
+            self.next_token();
+        }
+        while let Some(Token::ParenOpen | Token::CurlyBraceOpen) = self.peek_token() {
+            self.skip_group();
+        }
+    }
 
-        let mut body = Vec::new();
+    /// Leaves an error node for an unsupported expression which has just been
+    /// skipped, still parsing what follows it as in `[1, 2].size()`
+    fn parse_unsupported_postfix(&mut self, checkpoint: usize, start: usize) -> GResult<Expr> {
+        self.finish_node_at(checkpoint, SyntaxKind::Error);
+        let expr = Expr::new(ExprKind::Error, self.span_from(start));
+        self.parse_postfix(checkpoint, expr)
+    }
 
+    /// Parses any property accesses, method calls, `++` or `--` following
+    /// `expr`. As in groovyc, a `.` at the start of the next line continues
+    /// the expression
+    fn parse_postfix(&mut self, checkpoint: usize, mut expr: Expr) -> GResult<Expr> {
         loop {
-            if let Some(Token::CurlyBraceClose) | None = self.lexer.peek() {
-                self.lexer.next();
-                break;
+            let unsupported = match self.peek_token() {
+                Some(Token::SafeNavigation) => Some("safe navigation operators"),
+                Some(Token::SpreadDot) => Some("spread operators"),
+                Some(Token::DirectFieldAccess) => Some("direct field accesses"),
+                Some(Token::MethodPointer) => Some("method pointers"),
+                Some(Token::MethodReference) => Some("method references"),
+                Some(Token::SquareBraceOpen | Token::SafeIndex) => Some("subscripts"),
+                _ => None,
+            };
+            if let Some(what) = unsupported {
+                self.skip_unsupported_member(what);
+                self.finish_node_at(checkpoint, SyntaxKind::Error);
+                expr = Expr::new(ExprKind::Error, expr.span.to(self.prev_span));
+                continue;
+            }
+            if let Some(Token::ParenOpen | Token::CurlyBraceOpen) = self.peek_token() {
+                // only names can be called directly; `(a)(b)` is left to
+                // report the unexpected `(`
+                let (object, method) = match expr.kind {
+                    ExprKind::Variable { name } => (None, Spanned::new(name, expr.span)),
+                    ExprKind::Property { object, property } => (Some(object), property),
+                    kind => {
+                        expr.kind = kind;
+                        break;
+                    }
+                };
+                let mut arguments = if self.peek_token() == Some(&Token::ParenOpen) {
+                    self.parse_arguments()?
+                } else {
+                    Vec::new()
+                };
+                // a closure after the call is its last argument, as in
+                // `list.each { ... }`
+                if self.peek_token() == Some(&Token::CurlyBraceOpen) {
+                    let start = self.peek_span().start;
+                    let closure = self.checkpoint();
+                    self.skip_unsupported_expr("closures");
+                    self.finish_node_at(closure, SyntaxKind::Error);
+                    arguments.push(Expr::new(ExprKind::Error, self.span_from(start)));
+                }
+                self.finish_node_at(checkpoint, SyntaxKind::CallExpr);
+                expr = Expr::new(
+                    ExprKind::MethodCall {
+                        object,
+                        method,
+                        arguments,
+                        target: None,
+                    },
+                    expr.span.to(self.prev_span),
+                );
+                continue;
             }
-            body.push(self.parse_stmt()?);
+            if let Some(Token::PlusPlus | Token::MinusMinus) = self.peek_token() {
+                let op = match self.next_token() {
+                    Some(Token::PlusPlus) => UnaryOperator::Increment,
+                    _ => UnaryOperator::Decrement,
+                };
+                self.finish_node_at(checkpoint, SyntaxKind::PostfixExpr);
+                let span = expr.span.to(self.prev_span);
+                expr = Expr::new(
+                    ExprKind::Postfix {
+                        expr: Box::new(expr),
+                        op,
+                    },
+                    span,
+                );
+                continue;
+            }
+            if let (Some(Token::NewLine), Some(Token::Period)) =
+                (self.peek_token(), self.peek_nth(1))
+            {
+                self.next_token();
+            }
+            let (Some(Token::Period), Some(&Token::Identifier(name))) =
+                (self.peek_token(), self.peek_nth(1))
+            else {
+                break;
+            };
+            self.next_token();
+            self.next_token();
+            self.finish_node_at(checkpoint, SyntaxKind::PropertyExpr);
+            let span = expr.span.to(self.prev_span);
+            expr = Expr::new(
+                ExprKind::Property {
+                    object: Box::new(expr),
+                    property: Spanned::new(name, self.prev_span),
+                },
+                span,
+            );
         }
-
-        Ok(Stmt::Block { body, scope: None })
+        Ok(expr)
     }
 
-    fn parse_variable_declaration(&mut self, type_name: Type) -> GResult<Stmt> {
-        let name = self.expect_identifier()?;
-        self.expect_token(Token::SingleEqual)?;
-        let value = self.parse_expr()?;
-        Ok(Stmt::VariableDeclaration(Variable {
+    /// Parses a string containing interpolations, from its `GStringStart` to
+    /// its `GStringEnd`. The lexer guarantees that both are present, so an
+    /// error in one interpolation is recovered from without abandoning the
+    /// rest of the string
+    fn parse_gstring(&mut self) -> Expr {
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        self.next_token();
+
+        let mut parts = Vec::new();
+        loop {
+            let part = match self.peek_token() {
+                Some(Token::GStringPart(text)) => {
+                    let text = text.clone();
+                    self.next_token();
+                    InterpolatedStringPart::Literal(text)
+                }
+                Some(Token::Dollar) => {
+                    self.next_token();
+                    let path = self.checkpoint();
+                    let expr = if self.peek_token() == Some(&Token::Keyword(Keywords::This)) {
+                        self.diagnostics
+                            .push(self.unsupported("`this` expressions"));
+                        self.next_token();
+                        self.finish_node_at(path, SyntaxKind::Error);
+                        Expr::new(ExprKind::Error, self.prev_span)
+                    } else {
+                        match self.expect_identifier() {
+                            Ok(name) => {
+                                self.finish_node_at(path, SyntaxKind::NameRef);
+                                Expr::new(ExprKind::Variable { name: name.node }, name.span)
+                            }
+                            Err(err) => {
+                                self.diagnostics.push(err);
+                                self.next_token();
+                                Expr::new(ExprKind::Error, self.prev_span)
+                            }
+                        }
+                    };
+                    let expr = self.parse_postfix(path, expr).unwrap_or_else(|err| {
+                        self.diagnostics.push(err);
+                        Expr::new(ExprKind::Error, self.prev_span)
+                    });
+                    InterpolatedStringPart::Expression(Box::new(expr))
+                }
+                Some(Token::GStringInterpolationStart) => {
+                    self.next_token();
+                    InterpolatedStringPart::Expression(Box::new(self.parse_interpolation()))
+                }
+                Some(Token::GStringEnd) => {
+                    self.next_token();
+                    break;
+                }
+                _ => break,
+            };
+            parts.push(part);
+        }
+
+        self.finish_node_at(checkpoint, SyntaxKind::GStringExpr);
+        Expr::new(ExprKind::GString(GString(parts)), self.span_from(start))
+    }
+
+    /// Parses the expression inside `${ ... }`, up to and including the `}`
+    fn parse_interpolation(&mut self) -> Expr {
+        let start = self.prev_span.start;
+        let checkpoint = self.checkpoint();
+        if let Some(Token::CurlyBraceClose) = self.peek_token() {
+            // `${}` is allowed, and interpolates `null`
+            self.next_token();
+            return Expr::new(ExprKind::Empty, self.span_from(start));
+        }
+
+        let expr = self
+            .parse_expr()
+            .and_then(|expr| self.expect_token(Token::CurlyBraceClose).map(|_| expr));
+        match expr {
+            Ok(expr) => expr,
+            Err(err) => {
+                self.diagnostics.push(err);
+                // skip to the `}` which closes this interpolation
+                let mut depth = 0_usize;
+                loop {
+                    match self.peek_token() {
+                        Some(Token::CurlyBraceClose) if depth == 0 => {
+                            self.next_token();
+                            break;
+                        }
+                        Some(Token::GStringEnd) if depth == 0 => break,
+                        None => break,
+                        Some(Token::CurlyBraceOpen | Token::GStringInterpolationStart) => {
+                            depth += 1
+                        }
+                        Some(Token::CurlyBraceClose) => depth -= 1,
+                        Some(..) => {}
+                    }
+                    self.next_token();
+                }
+                self.finish_node_at(checkpoint, SyntaxKind::Error);
+                Expr::new(ExprKind::Error, self.span_from(start))
+            }
+        }
+    }
+
+    fn missing_expr(&mut self) -> Expr {
+        self.diagnostics.push(self.expected("expression"));
+        let checkpoint = self.checkpoint();
+        self.finish_node_at(checkpoint, SyntaxKind::Error);
+        Expr::new(ExprKind::Error, Span::empty(self.prev_span.end))
+    }
+}
+
+impl GroovyParser<'_> {
+    /// Parses a single statement. If the statement is malformed, the error is
+    /// recorded and an error statement covering the skipped tokens is returned
+    fn parse_stmt(&mut self) -> Stmt {
+        let start_pos = self.pos;
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        let kind = match self.parse_stmt_kind(start) {
+            Ok(..) if self.pos == start_pos => {
+                self.recover(self.expected("statement"), start_pos);
+                StmtKind::Error
+            }
+            Ok(..) if !self.at_stmt_end() => {
+                self.recover(self.expected("`;` or a line break"), start_pos);
+                StmtKind::Error
+            }
+            Ok(kind) => kind,
+            Err(err) => {
+                self.recover(err, start_pos);
+                StmtKind::Error
+            }
+        };
+        let node_kind = match kind {
+            StmtKind::Block { .. } => None,
+            StmtKind::VariableDeclaration(..) => Some(SyntaxKind::VarDecl),
+            StmtKind::Return { .. } => Some(SyntaxKind::ReturnStmt),
+            StmtKind::Assert { .. } => Some(SyntaxKind::AssertStmt),
+            StmtKind::Expression(..) => Some(SyntaxKind::ExprStmt),
+            StmtKind::If { .. } => Some(SyntaxKind::IfStmt),
+            StmtKind::While { .. } => Some(SyntaxKind::WhileStmt),
+            StmtKind::DoWhile { .. } => Some(SyntaxKind::DoWhileStmt),
+            StmtKind::For { .. } | StmtKind::ClassicFor { .. } => Some(SyntaxKind::ForStmt),
+            StmtKind::Break { .. } => Some(SyntaxKind::BreakStmt),
+            StmtKind::Continue { .. } => Some(SyntaxKind::ContinueStmt),
+            StmtKind::Labeled { .. } => Some(SyntaxKind::LabeledStmt),
+            _ => Some(SyntaxKind::Error),
+        };
+        if let Some(node_kind) = node_kind {
+            self.finish_node_at(checkpoint, node_kind);
+        }
+        Stmt::new(kind, self.span_from(start))
+    }
+
+    fn expect_stmt_end(&self) -> GResult<()> {
+        if self.at_stmt_end() {
+            Ok(())
+        } else {
+            Err(self.expected("`;` or a line break"))
+        }
+    }
+
+    /// Whether the statement which was just parsed has ended, as it must be
+    /// followed by a `;`, a line break or the end of its block. The branch of
+    /// an `if` may also be followed by `else` on the same line
+    fn at_stmt_end(&self) -> bool {
+        let prev = self.pos.checked_sub(1).map(|pos| &self.tokens[pos].token);
+        matches!(
+            self.peek_token(),
+            Some(
+                Token::ExprEnd
+                    | Token::NewLine
+                    | Token::CurlyBraceClose
+                    | Token::Keyword(Keywords::Else)
+            ) | None
+        ) || matches!(prev, Some(Token::ExprEnd | Token::CurlyBraceClose))
+    }
+
+    /// Parses the label following `break` or `continue`, if there is one on
+    /// the same line
+    fn parse_jump_label(&mut self) -> Option<Spanned<Identifier>> {
+        match self.peek_token() {
+            Some(Token::Identifier(..)) => self.expect_identifier().ok(),
+            _ => None,
+        }
+    }
+
+    /// Parses the parenthesized condition of an `if` or a loop
+    fn parse_condition(&mut self) -> GResult<Expr> {
+        self.expect_token(Token::ParenOpen)?;
+        let condition = self.parse_expr()?;
+        self.expect_token(Token::ParenClose)?;
+        Ok(condition)
+    }
+
+    /// Parses the branch of an `if` or the body of a loop, which is either a
+    /// block or a single statement, possibly starting on the next line
+    fn parse_body(&mut self) -> Stmt {
+        while self.peek_token() == Some(&Token::NewLine) {
+            self.next_token();
+        }
+        self.parse_stmt()
+    }
+
+    /// Parses either kind of `for` loop, starting at the `for` keyword
+    fn parse_for(&mut self) -> GResult<StmtKind> {
+        self.next_token();
+        self.expect_token(Token::ParenOpen)?;
+        if !self.at_for_in() {
+            return self.parse_classic_for();
+        }
+
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        let mut modifiers = Vec::new();
+        while self.peek_token() == Some(&Token::Keyword(Keywords::Final)) {
+            self.next_token();
+            modifiers.push(MethodModifier::Final);
+        }
+        let param_type = if self.at_for_in_variable() {
+            Type::Placeholder
+        } else {
+            self.parse_return_type()?
+        };
+        let name = self.expect_identifier()?;
+        self.finish_node_at(checkpoint, SyntaxKind::Param);
+        let variable = Parameter {
+            dynamically_typed: param_type == Type::Placeholder,
+            param_type,
+            name,
+            origin_type: None,
+            closure_shared: TODO_BOOL,
+            default_value: None,
+            in_static_context: TODO_BOOL,
+            modifiers,
+            annotations: Vec::new(),
+            is_varargs: false,
+            span: self.span_from(start),
+        };
+
+        // either `in` or `:`
+        self.next_token();
+        let collection = self.parse_expr()?;
+        self.expect_token(Token::ParenClose)?;
+        let loop_block = Box::new(self.parse_body());
+        Ok(StmtKind::For {
+            variable: Box::new(variable),
+            collection,
+            loop_block,
+            scope: None,
+        })
+    }
+
+    /// Whether the header of a `for` loop declares the variable of a for-in
+    /// loop, `x in`, `T x in` or `T x :`. Nothing is consumed
+    fn at_for_in(&mut self) -> bool {
+        let snapshot = self.snapshot();
+        while self.peek_token() == Some(&Token::Keyword(Keywords::Final)) {
+            self.next_token();
+        }
+        let is_for_in = self.at_for_in_variable()
+            || (self.parse_return_type().is_ok() && self.at_for_in_variable());
+        self.restore(snapshot);
+        is_for_in
+    }
+
+    fn at_for_in_variable(&self) -> bool {
+        matches!(self.peek_token(), Some(Token::Identifier(..)))
+            && matches!(
+                self.peek_nth(1),
+                Some(Token::Keyword(Keywords::In) | Token::Colon)
+            )
+    }
+
+    /// Parses the rest of a classic `for (init; condition; update)` loop,
+    /// following the `(`
+    fn parse_classic_for(&mut self) -> GResult<StmtKind> {
+        let init_start = self.pos;
+        let checkpoint = self.checkpoint();
+        let mut init = Vec::new();
+        let untyped = matches!(
+            self.peek_token(),
+            Some(Token::Keyword(Keywords::Def | Keywords::Var))
+        );
+        if untyped || self.at_variable_declaration() {
+            let start = self.peek_span().start;
+            let type_name = if untyped {
+                self.next_token();
+                Type::Placeholder
+            } else {
+                self.parse_type()?
+            };
+            loop {
+                let declarator = self.checkpoint();
+                let name = self.expect_identifier()?;
+                let value = if self.peek_token() == Some(&Token::SingleEqual) {
+                    self.next_token();
+                    self.parse_expr()?
+                } else {
+                    Expr::new(
+                        ExprKind::Constant(ConstExpr::Null),
+                        Span::empty(name.span.end),
+                    )
+                };
+                self.finish_node_at(declarator, SyntaxKind::Declarator);
+                let span = self.span_from(start);
+                init.push(Stmt::new(
+                    StmtKind::VariableDeclaration(Variable {
+                        name,
+                        type_name: type_name.clone(),
+                        value,
+                        in_static_context: TODO_BOOL,
+                        is_dynamically_typed: untyped,
+                        is_closure_shared_variable: TODO_BOOL,
+                        modifiers: Vec::new(),
+                        annotations: Vec::new(),
+                        span,
+                    }),
+                    span,
+                ));
+                if self.peek_token() != Some(&Token::Comma) {
+                    break;
+                }
+                self.next_token();
+            }
+        } else if self.peek_token() != Some(&Token::ExprEnd) {
+            for expr in self.parse_expr_list()? {
+                let span = expr.span;
+                init.push(Stmt::new(StmtKind::Expression(expr), span));
+            }
+        }
+        self.finish_node_at(checkpoint, SyntaxKind::ForInit);
+        self.expect_for_separator(init_start)?;
+
+        let condition_start = self.pos;
+        let condition = match self.peek_token() {
+            Some(Token::ExprEnd) => None,
+            _ => Some(self.parse_expr()?),
+        };
+        self.expect_for_separator(condition_start)?;
+
+        let checkpoint = self.checkpoint();
+        let update = match self.peek_token() {
+            Some(Token::ParenClose) => Vec::new(),
+            _ => self.parse_expr_list()?,
+        };
+        self.finish_node_at(checkpoint, SyntaxKind::ForUpdate);
+        self.expect_token(Token::ParenClose)?;
+
+        let loop_block = Box::new(self.parse_body());
+        Ok(StmtKind::ClassicFor {
+            init,
+            condition,
+            update,
+            loop_block,
+        })
+    }
+
+    /// Parses one or more expressions separated by commas
+    fn parse_expr_list(&mut self) -> GResult<Vec<Expr>> {
+        let mut exprs = vec![self.parse_expr()?];
+        while self.peek_token() == Some(&Token::Comma) {
+            self.next_token();
+            exprs.push(self.parse_expr()?);
+        }
+        Ok(exprs)
+    }
+
+    /// Expects a `;` separating the parts of a classic `for` header. An
+    /// expression consumes the `;` which follows it, so the separator may
+    /// already have been consumed if anything was parsed since `part_start`
+    fn expect_for_separator(&mut self, part_start: usize) -> GResult<()> {
+        if self.pos > part_start && self.tokens[self.pos - 1].token == Token::ExprEnd {
+            return Ok(());
+        }
+        self.expect_token(Token::ExprEnd)
+    }
+
+    fn parse_stmt_kind(&mut self, start: usize) -> GResult<StmtKind> {
+        Ok(match self.peek_token() {
+            Some(Token::CurlyBraceOpen) => self.parse_block()?.kind,
+            Some(Token::Keyword(Keywords::Assert)) => {
+                self.next_token();
+                let bool_expr = self.parse_expr()?;
+                let message = if let Some(Token::Colon | Token::Comma) = self.peek_token() {
+                    self.next_token();
+                    Some(self.parse_expr()?)
+                } else {
+                    None
+                };
+                StmtKind::Assert { bool_expr, message }
+            }
+            Some(Token::Keyword(Keywords::Return)) => {
+                self.next_token();
+                let expr = if self.at_stmt_end() {
+                    None
+                } else {
+                    Some(self.parse_expr()?)
+                };
+                StmtKind::Return { expr }
+            }
+            Some(Token::Keyword(Keywords::Break)) => {
+                self.next_token();
+                StmtKind::Break {
+                    label: self.parse_jump_label(),
+                }
+            }
+            Some(Token::Keyword(Keywords::Continue)) => {
+                self.next_token();
+                StmtKind::Continue {
+                    label: self.parse_jump_label(),
+                }
+            }
+            Some(Token::Keyword(Keywords::Class)) => {
+                self.skip_unsupported_stmt("local classes");
+                StmtKind::Error
+            }
+            // as in `private int count = 0` at the top of a script, which
+            // declares a local variable all the same
+            Some(Token::Keyword(
+                Keywords::Public | Keywords::Protected | Keywords::Private | Keywords::Static,
+            )) => {
+                self.diagnostics
+                    .push(self.unsupported("modifiers on local variables"));
+                self.next_token();
+                return self.parse_stmt_kind(start);
+            }
+            Some(&Token::Keyword(
+                keyword @ (Keywords::Throw
+                | Keywords::Try
+                | Keywords::Switch
+                | Keywords::Synchronized),
+            )) => {
+                self.skip_unsupported_stmt(&format!("`{}` statements", keyword.as_str()));
+                StmtKind::Error
+            }
+            Some(Token::Identifier(..)) if self.peek_nth(1) == Some(&Token::Colon) => {
+                let label = self.expect_identifier()?;
+                self.next_token();
+                let stmt = Box::new(self.parse_body());
+                StmtKind::Labeled { label, stmt }
+            }
+            Some(Token::Keyword(Keywords::If)) => {
+                self.next_token();
+                let expr = self.parse_condition()?;
+                let if_block = Box::new(self.parse_body());
+                let else_block =
+                    if self.peek_past_newlines() == Some(&Token::Keyword(Keywords::Else)) {
+                        self.skip_newlines();
+                        self.next_token();
+                        Some(Box::new(self.parse_body()))
+                    } else {
+                        None
+                    };
+                StmtKind::If {
+                    expr,
+                    if_block,
+                    else_block,
+                }
+            }
+            Some(Token::Keyword(Keywords::While)) => {
+                self.next_token();
+                let condition = self.parse_condition()?;
+                let loop_block = Box::new(self.parse_body());
+                StmtKind::While {
+                    condition,
+                    loop_block,
+                }
+            }
+            Some(Token::Keyword(Keywords::Do)) => {
+                self.next_token();
+                let loop_block = Box::new(self.parse_body());
+                self.skip_newlines();
+                self.expect_keyword(Keywords::While)?;
+                let condition = self.parse_condition()?;
+                StmtKind::DoWhile {
+                    loop_block,
+                    condition,
+                }
+            }
+            Some(Token::Keyword(Keywords::For)) => self.parse_for()?,
+            // only declarations can be annotated, as in `@Field int count = 0`
+            Some(Token::AtSign) => {
+                let annotations = self.parse_annotations()?;
+                if let Some(Token::Keyword(Keywords::Def | Keywords::Var)) = self.peek_token() {
+                    self.parse_untyped_declaration(start, annotations)?
+                } else if self.at_variable_declaration() {
+                    let type_name = self.parse_type()?;
+                    self.parse_variable_declaration(start, type_name, annotations)?
+                } else {
+                    return Err(self.expected("declaration"));
+                }
+            }
+            Some(Token::Keyword(Keywords::Def | Keywords::Var)) => {
+                self.parse_untyped_declaration(start, Vec::new())?
+            }
+            _ => {
+                if self.at_variable_declaration() {
+                    let type_name = self.parse_type()?;
+                    self.parse_variable_declaration(start, type_name, Vec::new())?
+                } else {
+                    let expr = self.parse_expr()?;
+                    if self.at_command_arguments(&expr) {
+                        self.skip_unsupported_stmt("command expressions");
+                        StmtKind::Error
+                    } else {
+                        StmtKind::Expression(expr)
+                    }
+                }
+            }
+        })
+    }
+
+    /// Whether `expr` names a method which is followed by arguments without
+    /// parentheses, as in `println 'hello'`
+    fn at_command_arguments(&self, expr: &Expr) -> bool {
+        matches!(
+            expr.kind,
+            ExprKind::Variable { .. } | ExprKind::Property { .. }
+        ) && matches!(
+            self.peek_token(),
+            Some(
+                Token::Literal(..)
+                    | Token::Identifier(..)
+                    | Token::GStringStart
+                    | Token::Keyword(Keywords::This | Keywords::Super | Keywords::New)
+            )
+        )
+    }
+
+    /// Parses a declaration with `def` or `var` in place of a type, which may
+    /// leave out the initial value
+    fn parse_untyped_declaration(
+        &mut self,
+        start: usize,
+        annotations: Vec<Annotation>,
+    ) -> GResult<StmtKind> {
+        self.next_token();
+        if self.peek_token() == Some(&Token::ParenOpen) {
+            self.skip_unsupported_stmt("multiple assignments");
+            return Ok(StmtKind::Error);
+        }
+        let name = self.expect_identifier()?;
+        let value = if let Some(Token::SingleEqual) = self.peek_token() {
+            self.next_token();
+            self.parse_expr()?
+        } else {
+            Expr::new(
+                ExprKind::Constant(ConstExpr::Null),
+                Span::empty(name.span.end),
+            )
+        };
+        Ok(StmtKind::VariableDeclaration(Variable {
+            name,
+            value,
+            is_closure_shared_variable: TODO_BOOL,
+            is_dynamically_typed: true,
+            in_static_context: TODO_BOOL,
+            type_name: Type::Placeholder,
+            modifiers: Vec::new(),
+            annotations,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_block(&mut self) -> GResult<Stmt> {
+        // a method body may open on the line after its declaration
+        self.skip_newlines();
+        let start = self.peek_span().start;
+        let checkpoint = self.checkpoint();
+        self.expect_token(Token::CurlyBraceOpen)?;
+        let open_brace = self.prev_span;
+
+        let mut body = Vec::new();
+
+        loop {
+            match self.peek_token() {
+                Some(Token::CurlyBraceClose) => {
+                    self.next_token();
+                    break;
+                }
+                None => {
+                    self.diagnostics.push(self.unclosed_brace(open_brace));
+                    break;
+                }
+                Some(Token::ExprEnd | Token::NewLine) => {
+                    self.next_token();
+                }
+                Some(..) => body.push(self.parse_stmt()),
+            }
+        }
+        self.finish_node_at(checkpoint, SyntaxKind::Block);
+
+        Ok(Stmt::new(
+            StmtKind::Block { body, scope: None },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_variable_declaration(
+        &mut self,
+        start: usize,
+        type_name: Type,
+        annotations: Vec<Annotation>,
+    ) -> GResult<StmtKind> {
+        let name = self.expect_identifier()?;
+        // as in Java, a local may be declared without a value, `String s`,
+        // and then holds its type's default until assigned
+        let value = if let Some(Token::SingleEqual) = self.peek_token() {
+            self.next_token();
+            self.parse_expr()?
+        } else {
+            Expr::new(
+                ExprKind::Constant(ConstExpr::Null),
+                Span::empty(name.span.end),
+            )
+        };
+        Ok(StmtKind::VariableDeclaration(Variable {
             name,
             type_name,
             value,
@@ -436,6 +2670,1444 @@ impl GroovyParser<'_> {
             in_static_context: TODO_BOOL,
             is_dynamically_typed: TODO_BOOL,
             modifiers: Vec::new(),
+            annotations,
+            span: self.span_from(start),
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(input: &str, span: Span) -> &str {
+        &input[span.start..span.end]
+    }
+
+    fn parse_class(input: &str) -> (Class, Vec<Diagnostic>) {
+        let Parse {
+            mut unit,
+            diagnostics,
+            ..
+        } = GroovyParser::parse(input);
+        assert!(!unit.types.is_empty(), "expected class, got {:?}", unit);
+        (unit.types.remove(0), diagnostics)
+    }
+
+    /// A reference to a class with no qualifier or type arguments
+    fn class_type(name: &str) -> Type {
+        Type::Class {
+            qualifier: Vec::new(),
+            name: Identifier::new(name),
+            arguments: None,
+        }
+    }
+
+    fn body(method: &Method) -> &[Stmt] {
+        match &method.body.as_ref().expect("method has no body").kind {
+            StmtKind::Block { body, .. } => body,
+            kind => panic!("expected block, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn class_and_method_spans() {
+        let input = "public class A {\n    int f(int x) {\n        return 1 + 2;\n    }\n}";
+        let (class, errors) = parse_class(input);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(class.span, Span::new(0, input.len()));
+        assert_eq!(text(input, class.name.span), "A");
+
+        let method = &class.methods[0];
+        assert!(text(input, method.span).starts_with("int f(int x) {"));
+        assert!(text(input, method.span).ends_with('}'));
+        assert_eq!(text(input, method.name.span), "f");
+        assert_eq!(text(input, method.parameters[0].span), "int x");
+        assert_eq!(text(input, method.parameters[0].name.span), "x");
+
+        let body = body(method);
+        assert_eq!(text(input, body[0].span), "return 1 + 2;");
+        match &body[0].kind {
+            StmtKind::Return { expr: Some(expr) } => {
+                assert_eq!(text(input, expr.span), "1 + 2")
+            }
+            kind => panic!("expected return, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn return_without_a_value() {
+        let input = "class A {\n  void f() { return }\n  void g(int a) {\n    if (a > 0) return;\n    return\n  }\n}";
+        let (class, errors) = parse_class(input);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(matches!(
+            body(&class.methods[0])[0].kind,
+            StmtKind::Return { expr: None }
+        ));
+        let g = body(&class.methods[1]);
+        match &g[0].kind {
+            StmtKind::If { if_block, .. } => {
+                assert!(matches!(if_block.kind, StmtKind::Return { expr: None }))
+            }
+            kind => panic!("expected if, got {:?}", kind),
+        }
+        assert!(matches!(g[1].kind, StmtKind::Return { expr: None }));
+    }
+
+    #[test]
+    fn locals_without_a_value() {
+        let input =
+            "class A {\n  void f() {\n    String s\n    int[] xs; def x\n    s = 'a'\n  }\n}";
+        let (class, errors) = parse_class(input);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let stmts = body(&class.methods[0]);
+        let names: Vec<&str> = stmts[..3]
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                StmtKind::VariableDeclaration(var) => {
+                    assert_eq!(var.value.kind, ExprKind::Constant(ConstExpr::Null));
+                    assert!(var.value.span.is_empty());
+                    text(input, var.name.span)
+                }
+                kind => panic!("expected declaration, got {:?}", kind),
+            })
+            .collect();
+        assert_eq!(names, ["s", "xs", "x"]);
+        assert!(matches!(stmts[3].kind, StmtKind::Expression(..)));
+    }
+
+    #[test]
+    fn recovers_within_method_body() {
+        let input = "class A {
+            void f(int a) {
+                int x = ;
+                foo bar baz
+                return 1
+            }
+            void g(int a) {}
+        }";
+        let (class, errors) = parse_class(input);
+        assert_eq!(class.methods.len(), 2);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+
+        let body = body(&class.methods[0]);
+        assert_eq!(body.len(), 3);
+        match &body[0].kind {
+            StmtKind::VariableDeclaration(var) => {
+                assert!(matches!(var.value.kind, ExprKind::Error))
+            }
+            kind => panic!("expected declaration, got {:?}", kind),
+        }
+        assert!(matches!(body[1].kind, StmtKind::Error));
+        assert_eq!(text(input, body[1].span), "foo bar baz");
+        assert!(matches!(body[2].kind, StmtKind::Return { .. }));
+    }
+
+    #[test]
+    fn recovers_from_bad_members_and_toplevel_garbage() {
+        let input = "} class A {\n int 5\n void f(int a) {}\n}\n% class B {}";
+        let Parse {
+            unit,
+            diagnostics: errors,
+            ..
+        } = GroovyParser::parse(input);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert_eq!(unit.types.len(), 2);
+        assert_eq!(unit.types[0].methods.len(), 1);
+    }
+
+    #[test]
+    fn scripts_mix_statements_methods_and_classes() {
+        let input = "class A {}\ndef x = 1\nint twice(int a) {\n  return a * 2\n}\nx + 1\nclass B {}\ndef f(long b) {}\n";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+
+        let unit = &parse.unit;
+        assert_eq!(unit.types.len(), 2);
+        assert_eq!(unit.types[1].name.node, Identifier::new("B"));
+        let methods: Vec<&str> = unit
+            .methods
+            .iter()
+            .map(|method| text(input, method.name.span))
+            .collect();
+        assert_eq!(methods, ["twice", "f"]);
+        assert_eq!(unit.methods[1].return_type, Type::Placeholder);
+        let statements: Vec<&str> = unit
+            .statements
+            .iter()
+            .map(|stmt| text(input, stmt.span))
+            .collect();
+        assert_eq!(statements, ["def x = 1", "x + 1"]);
+
+        let file = parse.source_file();
+        assert_eq!(file.classes().count(), 2);
+        assert_eq!(file.methods().count(), 2);
+        assert_eq!(file.statements().count(), 2);
+    }
+
+    #[test]
+    fn interfaces_enums_and_traits() {
+        let input = "interface Shape {\n  double area(int scale)\n  default String describe(int a) { return 'shape' }\n}\nenum Color {\n  RED(255, 0), GREEN,\n  BLUE { int hex(int a) { return 255 } }\n  private final int red = 0\n  Color(int r, int g) {}\n  int hex(int a) { return red }\n}\ntrait Named {\n  String name\n  abstract String greet(int a)\n  String hello(int a) { return name }\n}\ndef trait = 1\n";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+        let types = &parse.unit.types;
+        let kinds: Vec<ClassKind> = types.iter().map(|class| class.kind).collect();
+        assert_eq!(
+            kinds,
+            [ClassKind::Interface, ClassKind::Enum, ClassKind::Trait]
+        );
+
+        let shape = &types[0];
+        assert!(shape.methods[0].body.is_none());
+        assert_eq!(shape.methods[1].modifiers, [MethodModifier::Default]);
+        assert!(shape.methods[1].body.is_some());
+
+        let color = &types[1];
+        let constants: Vec<&str> = color
+            .enum_constants
+            .iter()
+            .map(|constant| text(input, constant.name.span))
+            .collect();
+        assert_eq!(constants, ["RED", "GREEN", "BLUE"]);
+        assert_eq!(color.enum_constants[0].arguments.len(), 2);
+        let blue = color.enum_constants[2].body.as_ref().unwrap();
+        assert_eq!(text(input, blue.methods[0].name.span), "hex");
+        assert_eq!(text(input, color.fields[0].name.span), "red");
+        assert!(color.fields[0].initial_value.is_some());
+        let methods: Vec<&str> = color
+            .methods
+            .iter()
+            .map(|method| text(input, method.name.span))
+            .collect();
+        assert_eq!(methods, ["Color", "hex"]);
+        assert!(color.methods[0].is_constructor);
+
+        let named = &types[2];
+        assert_eq!(text(input, named.properties[0].field.name.span), "name");
+        assert_eq!(named.methods[0].modifiers, [MethodModifier::Abstract]);
+        assert!(named.methods[0].body.is_none());
+        assert!(named.methods[1].body.is_some());
+        // `trait` is still an ordinary name elsewhere
+        assert_eq!(parse.unit.statements.len(), 1);
+
+        let file = parse.source_file();
+        let color = file.classes().nth(1).unwrap();
+        assert_eq!(color.enum_constants().count(), 3);
+        assert_eq!(color.fields().count(), 1);
+    }
+
+    #[test]
+    fn enum_constant_separators() {
+        let input =
+            "enum E { A, B, }\nenum F { X; void f() {} }\nenum G { ; static int g() { return 1 } }";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+        let members: Vec<(Vec<&str>, Vec<&str>)> = parse
+            .unit
+            .types
+            .iter()
+            .map(|class| {
+                (
+                    class
+                        .enum_constants
+                        .iter()
+                        .map(|constant| text(input, constant.name.span))
+                        .collect(),
+                    class
+                        .methods
+                        .iter()
+                        .map(|method| text(input, method.name.span))
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            members,
+            [
+                (vec!["A", "B"], vec![]),
+                (vec!["X"], vec!["f"]),
+                (vec![], vec!["g"])
+            ]
+        );
+    }
+
+    #[test]
+    fn recovers_within_enums_interfaces_and_traits() {
+        let input = "enum Color {\n  RED(1,, 2)\n  GREEN { int f() { return 1 } }, BLUE;\n  int x = 1\n}\ninterface I {\n  int f(int a b)\n  void g()\n}\ntrait T {\n  String 1name\n  def ok() {}\n}\nclass After {}";
+        let parse = GroovyParser::parse(input);
+        let messages: Vec<&str> = parse
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "invalid suffix `name` on numeric literal",
+                "expected expression, found `,`",
+                "expected `,` or `)`, found identifier `b`",
+                "expected identifier, found number `1`",
+            ]
+        );
+
+        let types = &parse.unit.types;
+        let names: Vec<&str> = types
+            .iter()
+            .map(|class| text(input, class.name.span))
+            .collect();
+        assert_eq!(names, ["Color", "I", "T", "After"]);
+        // a bad constant does not end the constants on the lines after it
+        let constants: Vec<&str> = types[0]
+            .enum_constants
+            .iter()
+            .map(|constant| text(input, constant.name.span))
+            .collect();
+        assert_eq!(constants, ["GREEN", "BLUE"]);
+        assert_eq!(types[0].properties.len(), 1);
+        assert_eq!(text(input, types[1].methods[0].name.span), "g");
+        assert_eq!(text(input, types[2].methods[0].name.span), "ok");
+    }
+
+    #[test]
+    fn fields_properties_constructors_and_initializers() {
+        let input = "class A {\n  private int a = 1, b, c = a + 1\n  String name\n  def untyped = 2\n  static count = 0\n  public final long d\n  static { int z = 1 }\n  { int x = 2 }\n  A(int a) { int y = a }\n  public A(long a, long b) {}\n  int A = 3\n}\ninterface I {\n  int MAX = 10\n}";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+        let class = &parse.unit.types[0];
+
+        let fields: Vec<(&str, Option<&str>)> = class
+            .fields
+            .iter()
+            .map(|field| {
+                (
+                    text(input, field.name.span),
+                    field
+                        .initial_value
+                        .as_ref()
+                        .map(|value| text(input, value.span)),
+                )
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("a", Some("1")),
+                ("b", None),
+                ("c", Some("a + 1")),
+                ("d", None)
+            ]
+        );
+        assert!(class.fields[..3]
+            .iter()
+            .all(|field| field.field_type == Type::Int));
+
+        let properties: Vec<(&str, &Type)> = class
+            .properties
+            .iter()
+            .map(|property| {
+                (
+                    text(input, property.field.name.span),
+                    &property.field.field_type,
+                )
+            })
+            .collect();
+        assert_eq!(
+            properties,
+            [
+                ("name", &class_type("String")),
+                ("untyped", &Type::Placeholder),
+                ("count", &Type::Placeholder),
+                ("A", &Type::Int),
+            ]
+        );
+
+        let statics: Vec<bool> = class
+            .initializers
+            .iter()
+            .map(|initializer| initializer.is_static)
+            .collect();
+        assert_eq!(statics, [true, false]);
+
+        assert_eq!(class.methods.len(), 2);
+        assert!(class.methods.iter().all(|method| method.is_constructor));
+        assert_eq!(class.methods[1].visibility, Visibility::Public);
+        assert_eq!(class.methods[1].parameters.len(), 2);
+
+        // interface fields are constants rather than properties
+        let interface = &parse.unit.types[1];
+        assert_eq!(interface.fields.len(), 1);
+        assert!(interface.properties.is_empty());
+
+        let file = parse.source_file();
+        let class = file.classes().next().unwrap();
+        let names: Vec<String> = class
+            .fields()
+            .next()
+            .unwrap()
+            .declarators()
+            .map(|declarator| declarator.name().unwrap().text().to_owned())
+            .collect();
+        assert_eq!(names, ["a", "b", "c"]);
+        let initializers: Vec<bool> = class.initializers().map(|init| init.is_static()).collect();
+        assert_eq!(initializers, [true, false]);
+    }
+
+    #[test]
+    fn recovers_from_malformed_members() {
+        let input = "class A {\n  private int = 1\n  int b = 2, = 3\n  static { int x = }\n  A(int a, ) {}\n  String name\n  A() {}\n}";
+        let (class, errors) = parse_class(input);
+        let messages: Vec<&str> = errors.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "expected identifier, found `=`",
+                "expected identifier, found `=`",
+                "expected expression, found `}`",
+                "expected type, found `)`",
+            ]
+        );
+        assert!(class.fields.is_empty());
+        let properties: Vec<&str> = class
+            .properties
+            .iter()
+            .map(|property| text(input, property.field.name.span))
+            .collect();
+        assert_eq!(properties, ["b", "name"]);
+        // the initializer is kept despite the error inside it
+        assert_eq!(class.initializers.len(), 1);
+        assert_eq!(class.methods.len(), 1);
+        assert!(class.methods[0].parameters.is_empty());
+    }
+
+    #[test]
+    fn records_and_sealed_types() {
+        let input = "sealed interface Shape permits Circle,\n    Square {}\nfinal class Circle implements Shape {}\nnon-sealed class Square extends Base implements Shape, Cloneable {}\nrecord Point(int x, int y) implements Shape {\n  public Point {\n    assert x + y\n  }\n  int sum(int a) { return x + y }\n}\ndef record = 1\n";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+        let types = &parse.unit.types;
+
+        let shape = &types[0];
+        assert_eq!(shape.modifiers, [ClassModifier::Sealed]);
+        let permits: Vec<&str> = shape
+            .permits
+            .iter()
+            .map(|ty| text(input, ty.span))
+            .collect();
+        assert_eq!(permits, ["Circle", "Square"]);
+
+        let square = &types[2];
+        assert_eq!(square.modifiers, [ClassModifier::NonSealed]);
+        assert_eq!(
+            square.super_class.as_ref().map(|ty| text(input, ty.span)),
+            Some("Base")
+        );
+        assert_eq!(square.interfaces.len(), 2);
+
+        let point = &types[3];
+        assert_eq!(point.kind, ClassKind::Record);
+        let components: Vec<&str> = point
+            .record_components
+            .iter()
+            .map(|param| text(input, param.name.span))
+            .collect();
+        assert_eq!(components, ["x", "y"]);
+        assert!(point.methods[0].is_constructor);
+        assert_eq!(point.methods[0].visibility, Visibility::Public);
+        assert_eq!(text(input, point.methods[1].name.span), "sum");
+        assert_eq!(parse.unit.statements.len(), 1);
+
+        let file = parse.source_file();
+        let names: Vec<String> = file
+            .classes()
+            .map(|class| class.name().unwrap().text().to_owned())
+            .collect();
+        assert_eq!(names, ["Shape", "Circle", "Square", "Point"]);
+        let shape = file.classes().next().unwrap();
+        assert_eq!(shape.permits_clause().unwrap().types().count(), 2);
+        assert_eq!(parse.syntax().to_string(), input);
+    }
+
+    #[test]
+    fn generic_class_headers() {
+        let input = "class Box<T extends Comparable<T>> extends Base<T> implements Iterable<Map<String, T>>, Serializable {}\ninterface Pair<K, V extends Number & Comparable<V>> extends Tree<K> {}\n";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+        let types = &parse.unit.types;
+
+        let boxed = &types[0];
+        let param = &boxed.type_parameters[0];
+        assert_eq!(text(input, param.name.span), "T");
+        assert_eq!(
+            param.bounds[0].node,
+            Type::Class {
+                qualifier: Vec::new(),
+                name: Identifier::new("Comparable"),
+                arguments: Some(vec![class_type("T")]),
+            }
+        );
+        assert_eq!(text(input, param.span), "T extends Comparable<T>");
+        let super_class = boxed.super_class.as_ref().unwrap();
+        assert_eq!(text(input, super_class.span), "Base<T>");
+        let interfaces: Vec<&str> = boxed
+            .interfaces
+            .iter()
+            .map(|ty| text(input, ty.span))
+            .collect();
+        assert_eq!(interfaces, ["Iterable<Map<String, T>>", "Serializable"]);
+
+        let pair = &types[1];
+        let bounds: Vec<&str> = pair.type_parameters[1]
+            .bounds
+            .iter()
+            .map(|ty| text(input, ty.span))
+            .collect();
+        assert_eq!(bounds, ["Number", "Comparable<V>"]);
+        assert_eq!(
+            pair.interfaces[0].node.class_name(),
+            Some(Identifier::new("Tree"))
+        );
+
+        let file = parse.source_file();
+        let class = file.classes().next().unwrap();
+        let param = class.type_param_list().unwrap().params().next().unwrap();
+        let bound = param.bounds().next().unwrap();
+        assert_eq!(bound.name().unwrap().text(), "Comparable");
+        assert_eq!(bound.type_arg_list().unwrap().types().count(), 1);
+        assert_eq!(parse.syntax().to_string(), input);
+    }
+
+    #[test]
+    fn qualified_generic_and_array_types() {
+        let input = "class A {\n  java.util.Map<String, ? extends Number>[][] f(List<? super T> a, List<?> b, int[] c, String... rest) {\n    List<String> x = y\n  }\n  void g(int... a, int b) {}\n}";
+        let (class, errors) = parse_class(input);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].message, "only the last parameter can be varargs");
+        assert_eq!(text(input, errors[0].span), "...");
+
+        let method = &class.methods[0];
+        let map = Type::Class {
+            qualifier: vec![Identifier::new("java"), Identifier::new("util")],
+            name: Identifier::new("Map"),
+            arguments: Some(vec![
+                class_type("String"),
+                Type::Wildcard(Some(WildcardBound::Extends(Box::new(class_type("Number"))))),
+            ]),
+        };
+        assert_eq!(
+            method.return_type,
+            Type::Array(Box::new(Type::Array(Box::new(map))))
+        );
+        let list = |argument| Type::Class {
+            qualifier: Vec::new(),
+            name: Identifier::new("List"),
+            arguments: Some(vec![argument]),
+        };
+        let params: Vec<&Type> = method
+            .parameters
+            .iter()
+            .map(|param| &param.param_type)
+            .collect();
+        assert_eq!(
+            params,
+            [
+                &list(Type::Wildcard(Some(WildcardBound::Super(Box::new(
+                    class_type("T")
+                ))))),
+                &list(Type::Wildcard(None)),
+                &Type::Array(Box::new(Type::Int)),
+                &Type::Array(Box::new(class_type("String"))),
+            ]
+        );
+        assert!(method.parameters[3].is_varargs);
+        assert!(!method.parameters[2].is_varargs);
+
+        match &body(method)[0].kind {
+            StmtKind::VariableDeclaration(var) => {
+                assert_eq!(var.type_name, list(class_type("String")))
+            }
+            kind => panic!("expected declaration, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn parameter_features() {
+        let input = "class A {\n  def m(String a, int b = 2, Object... rest) {}\n  void n() throws IOException, java.sql.SQLException {}\n  void o(final int a, @Nullable @javax.annotation.Nonnull String b, c, def d, final e = 1) {}\n  def p(final @Ann String a, @Ann final b) {}\n}";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+        let methods = &parse.unit.types[0].methods;
+
+        let m = &methods[0];
+        assert!(m.has_default_value);
+        let default = m.parameters[1].default_value.as_ref().unwrap();
+        assert_eq!(text(input, default.span), "2");
+        assert!(m.parameters[2].is_varargs);
+
+        let n = &methods[1];
+        assert!(n.parameters.is_empty());
+        assert!(!n.has_default_value);
+        let exceptions: Vec<&str> = n
+            .exceptions
+            .iter()
+            .map(|exception| text(input, exception.span))
+            .collect();
+        assert_eq!(exceptions, ["IOException", "java.sql.SQLException"]);
+
+        let o = &methods[2];
+        assert_eq!(o.parameters[0].modifiers, [MethodModifier::Final]);
+        assert_eq!(o.parameters[0].param_type, Type::Int);
+        let annotations: Vec<String> = o.parameters[1]
+            .annotations
+            .iter()
+            .map(|annotation| annotation.name.to_string())
+            .collect();
+        assert_eq!(annotations, ["Nullable", "javax.annotation.Nonnull"]);
+        assert_eq!(
+            text(input, o.parameters[1].span),
+            "@Nullable @javax.annotation.Nonnull String b"
+        );
+        let untyped: Vec<bool> = o
+            .parameters
+            .iter()
+            .map(|param| param.param_type == Type::Placeholder && param.dynamically_typed)
+            .collect();
+        assert_eq!(untyped, [false, false, true, true, true]);
+        assert_eq!(o.parameters[4].modifiers, [MethodModifier::Final]);
+        assert!(o.has_default_value);
+
+        let p = &methods[3];
+        for param in &p.parameters {
+            assert_eq!(param.modifiers, [MethodModifier::Final]);
+            assert_eq!(param.annotations.len(), 1);
+        }
+        assert!(p.parameters[1].dynamically_typed);
+        assert_eq!(text(input, p.parameters[0].span), "final @Ann String a");
+
+        let file = parse.source_file();
+        let class = file.classes().next().unwrap();
+        let n = class.methods().nth(1).unwrap();
+        assert_eq!(n.param_list().unwrap().params().count(), 0);
+        assert_eq!(n.throws_clause().unwrap().types().count(), 2);
+        let b = class
+            .methods()
+            .nth(2)
+            .unwrap()
+            .param_list()
+            .unwrap()
+            .params()
+            .nth(1)
+            .unwrap();
+        assert_eq!(b.annotations().count(), 2);
+        assert_eq!(b.name().unwrap().text(), "b");
+    }
+
+    #[test]
+    fn recovers_from_malformed_parameters_and_throws() {
+        let input = "class A {\n  void f(int a = , String b) {}\n  void g(int... a, int b) {}\n  void h() throws {}\n  void k(String args[]) {}\n}";
+        let (class, errors) = parse_class(input);
+        let messages: Vec<&str> = errors.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "expected expression, found `,`",
+                "only the last parameter can be varargs",
+                "expected type, found `{`",
+            ]
+        );
+        let methods: Vec<(&str, usize)> = class
+            .methods
+            .iter()
+            .map(|method| (text(input, method.name.span), method.parameters.len()))
+            .collect();
+        assert_eq!(methods, [("g", 2), ("k", 1)]);
+        // a misplaced varargs parameter is still reported as one
+        assert!(class.methods[0].parameters[0].is_varargs);
+        assert_eq!(
+            class.methods[1].parameters[0].param_type,
+            Type::Array(Box::new(class_type("String")))
+        );
+    }
+
+    #[test]
+    fn annotations_on_declarations() {
+        let input = "@Deprecated
+package app
+
+@Grab(group = 'org.slf4j', module = 'slf4j-api')
+import org.slf4j.Logger
+
+@Field int count = 0
+
+@CompileStatic
+@Deprecated
+class A {
+    @SuppressWarnings(['unchecked', 'rawtypes'])
+    private List x, y
+
+    @Memoized(maxCacheSize = { 10 })
+    def f(@NotNull String s) {
+        @Lazy def local = 1
+    }
+}
+
+enum E { @Deprecated RED, GREEN }";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+        let unit = &parse.unit;
+        let package = unit.package.as_ref().unwrap();
+        assert_eq!(package.annotations[0].name.to_string(), "Deprecated");
+
+        let grab = &unit.imports[0].annotations[0];
+        assert_eq!(grab.name.to_string(), "Grab");
+        let names: Vec<&str> = grab
+            .elements
+            .iter()
+            .map(|element| text(input, element.name.unwrap().span))
+            .collect();
+        assert_eq!(names, ["group", "module"]);
+
+        match &unit.statements[0].kind {
+            StmtKind::VariableDeclaration(var) => {
+                assert_eq!(var.annotations[0].name.to_string(), "Field");
+                assert_eq!(var.type_name, Type::Int);
+            }
+            kind => panic!("expected declaration, got {:?}", kind),
+        }
+
+        let class = &unit.types[0];
+        assert_eq!(class.annotations.len(), 2);
+        assert_eq!(class.fields.len(), 2);
+        for field in &class.fields {
+            let element = &field.annotations[0].elements[0];
+            assert!(element.name.is_none());
+            assert!(matches!(&element.value, AnnotationValue::Array(values) if values.len() == 2));
+        }
+
+        let f = &class.methods[0];
+        assert!(matches!(
+            f.annotations[0].elements[0].value,
+            AnnotationValue::Closure(..)
+        ));
+        assert_eq!(f.parameters[0].annotations[0].name.to_string(), "NotNull");
+        match &body(f)[0].kind {
+            StmtKind::VariableDeclaration(var) => {
+                assert_eq!(var.annotations[0].name.to_string(), "Lazy")
+            }
+            kind => panic!("expected declaration, got {:?}", kind),
+        }
+
+        let constants = &unit.types[1].enum_constants;
+        assert_eq!(constants.len(), 2);
+        assert_eq!(constants[0].annotations[0].name.to_string(), "Deprecated");
+        assert_eq!(text(input, constants[0].span), "@Deprecated RED");
+
+        let file = parse.source_file();
+        let class = file.classes().next().unwrap();
+        assert_eq!(class.name().unwrap().text(), "A");
+        assert_eq!(class.annotations().count(), 2);
+        let field = class.fields().next().unwrap();
+        let annotation = field.annotations().next().unwrap();
+        assert!(annotation.elements().next().unwrap().name().is_none());
+    }
+
+    #[test]
+    fn lookahead_does_not_repeat_diagnostics() {
+        for input in [
+            "@Ann(new Foo()) def f() {}",
+            "@Ann(a = ) def f() {}",
+            "@Ann(new Foo()) class A {}",
+            "@Ann(a = ) class A {}",
+        ] {
+            let parse = GroovyParser::parse(input);
+            assert_eq!(
+                parse.diagnostics.len(),
+                1,
+                "{:?}: {:?}",
+                input,
+                parse.diagnostics
+            );
+        }
+    }
+
+    #[test]
+    fn annotation_type_declarations() {
+        let input = "@Retention(RetentionPolicy.RUNTIME)
+@interface Tag {
+    String value() default ''
+    int[] sizes() default [1, 2]
+    Class<?> type()
+}";
+        let (class, errors) = parse_class(input);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(class.kind, ClassKind::Annotation);
+        assert_eq!(text(input, class.name.span), "Tag");
+        assert_eq!(class.annotations[0].name.to_string(), "Retention");
+
+        let defaults: Vec<Option<&AnnotationValue>> = class
+            .methods
+            .iter()
+            .map(|method| method.annotation_default.as_ref())
+            .collect();
+        assert!(matches!(defaults[0], Some(AnnotationValue::Expr(..))));
+        assert!(matches!(defaults[1], Some(AnnotationValue::Array(..))));
+        assert!(defaults[2].is_none());
+
+        let (_, errors) = parse_class("@interface A extends B {}");
+        assert_eq!(
+            errors[0].message,
+            "annotation types cannot extend other types"
+        );
+    }
+
+    #[test]
+    fn control_flow_statements() {
+        let input = "class A {
+    void f(List xs, int n) {
+        if (n > 0) {
+            n = 1
+        } else if (n == 0) n = 2
+        else {
+            n += 3
+        }
+        while (n < 10) n++
+        do {
+            --n
+        } while (n >= 0 && n != 5)
+        for (int i = 0, j = n; i < j; i++, j--) {}
+        for (;;) {}
+        for (x in xs) {}
+        for (String s : xs) {}
+        for (final def y in 0..<n) {}
+    }
+}";
+        let (class, errors) = parse_class(input);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let body = body(&class.methods[0]);
+
+        match &body[0].kind {
+            StmtKind::If {
+                expr, else_block, ..
+            } => {
+                assert_eq!(text(input, expr.span), "n > 0");
+                let else_if = else_block.as_ref().unwrap();
+                assert!(text(input, else_if.span).starts_with("if (n == 0) n = 2\n"));
+                match &else_if.kind {
+                    StmtKind::If {
+                        if_block,
+                        else_block,
+                        ..
+                    } => {
+                        assert_eq!(text(input, if_block.span), "n = 2");
+                        assert!(else_block.is_some());
+                    }
+                    kind => panic!("expected if, got {:?}", kind),
+                }
+            }
+            kind => panic!("expected if, got {:?}", kind),
+        }
+
+        match &body[1].kind {
+            StmtKind::While {
+                condition,
+                loop_block,
+            } => {
+                assert_eq!(text(input, condition.span), "n < 10");
+                match &loop_block.kind {
+                    StmtKind::Expression(expr) => assert!(matches!(
+                        expr.kind,
+                        ExprKind::Postfix {
+                            op: UnaryOperator::Increment,
+                            ..
+                        }
+                    )),
+                    kind => panic!("expected expression, got {:?}", kind),
+                }
+            }
+            kind => panic!("expected while, got {:?}", kind),
+        }
+
+        match &body[2].kind {
+            StmtKind::DoWhile { condition, .. } => {
+                assert_eq!(text(input, condition.span), "n >= 0 && n != 5");
+                assert!(matches!(
+                    condition.kind,
+                    ExprKind::BinaryOp {
+                        op: BinaryOperator::LogicalAnd,
+                        ..
+                    }
+                ));
+            }
+            kind => panic!("expected do-while, got {:?}", kind),
+        }
+
+        match &body[3].kind {
+            StmtKind::ClassicFor {
+                init,
+                condition,
+                update,
+                ..
+            } => {
+                let names: Vec<&str> = init
+                    .iter()
+                    .map(|stmt| match &stmt.kind {
+                        StmtKind::VariableDeclaration(var) => text(input, var.name.span),
+                        kind => panic!("expected declaration, got {:?}", kind),
+                    })
+                    .collect();
+                assert_eq!(names, ["i", "j"]);
+                assert_eq!(text(input, condition.as_ref().unwrap().span), "i < j");
+                assert_eq!(update.len(), 2);
+            }
+            kind => panic!("expected for, got {:?}", kind),
+        }
+
+        match &body[4].kind {
+            StmtKind::ClassicFor {
+                init,
+                condition,
+                update,
+                ..
+            } => assert!(init.is_empty() && condition.is_none() && update.is_empty()),
+            kind => panic!("expected for, got {:?}", kind),
+        }
+
+        let loops: Vec<(&str, bool, &str)> = body[5..]
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                StmtKind::For {
+                    variable,
+                    collection,
+                    ..
+                } => (
+                    text(input, variable.name.span),
+                    variable.dynamically_typed,
+                    text(input, collection.span),
+                ),
+                kind => panic!("expected for, got {:?}", kind),
+            })
+            .collect();
+        assert_eq!(
+            loops,
+            [("x", true, "xs"), ("s", false, "xs"), ("y", true, "0..<n")]
+        );
+
+        let file = GroovyParser::parse(input).source_file();
+        let method = file.classes().next().unwrap().methods().next().unwrap();
+        let statements: Vec<syntax::Stmt> = method.body().unwrap().statements().collect();
+        let syntax::Stmt::IfStmt(if_stmt) = &statements[0] else {
+            panic!("expected if, got {:?}", statements[0]);
+        };
+        assert!(matches!(
+            if_stmt.else_branch(),
+            Some(syntax::Stmt::IfStmt(..))
+        ));
+        let syntax::Stmt::ForStmt(classic) = &statements[3] else {
+            panic!("expected for, got {:?}", statements[3]);
+        };
+        assert_eq!(classic.init().unwrap().declarators().count(), 2);
+        assert_eq!(classic.update().unwrap().exprs().count(), 2);
+        assert!(classic.variable().is_none() && classic.condition().is_some());
+        let syntax::Stmt::ForStmt(for_in) = &statements[5] else {
+            panic!("expected for, got {:?}", statements[5]);
+        };
+        assert_eq!(for_in.variable().unwrap().name().unwrap().text(), "x");
+        assert!(for_in.collection().is_some() && for_in.condition().is_none());
+    }
+
+    #[test]
+    fn break_and_continue_in_loops() {
+        let input = "outer:
+for (row in rows) {
+    for (int i = 0; i < row; i++) {
+        if (i == 2) continue
+        if (i > 5) break outer
+        continue outer;
+    }
+    while (true) break
+}";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+        let StmtKind::Labeled { label, stmt } = &parse.unit.statements[0].kind else {
+            panic!("expected label, got {:?}", parse.unit.statements[0]);
+        };
+        assert_eq!(text(input, label.span), "outer");
+        let StmtKind::For { loop_block, .. } = &stmt.kind else {
+            panic!("expected for, got {:?}", stmt);
+        };
+        let StmtKind::Block { body: outer, .. } = &loop_block.kind else {
+            panic!("expected block, got {:?}", loop_block);
+        };
+        let StmtKind::ClassicFor { loop_block, .. } = &outer[0].kind else {
+            panic!("expected for, got {:?}", outer[0]);
+        };
+        let StmtKind::Block { body: inner, .. } = &loop_block.kind else {
+            panic!("expected block, got {:?}", loop_block);
+        };
+
+        let jumps: Vec<(&str, Option<&str>)> = inner
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                StmtKind::If { if_block, .. } => &if_block.kind,
+                kind => kind,
+            })
+            .map(|kind| match kind {
+                StmtKind::Break { label } => ("break", label.map(|l| text(input, l.span))),
+                StmtKind::Continue { label } => ("continue", label.map(|l| text(input, l.span))),
+                kind => panic!("expected break or continue, got {:?}", kind),
+            })
+            .collect();
+        assert_eq!(
+            jumps,
+            [
+                ("continue", None),
+                ("break", Some("outer")),
+                ("continue", Some("outer"))
+            ]
+        );
+        let StmtKind::While { loop_block, .. } = &outer[1].kind else {
+            panic!("expected while, got {:?}", outer[1]);
+        };
+        assert!(matches!(loop_block.kind, StmtKind::Break { label: None }));
+
+        let file = parse.source_file();
+        let Some(syntax::Stmt::LabeledStmt(labeled)) = file.statements().next() else {
+            panic!("expected labeled statement");
+        };
+        assert_eq!(labeled.label().unwrap().text(), "outer");
+        assert!(matches!(labeled.stmt(), Some(syntax::Stmt::ForStmt(..))));
+    }
+
+    #[test]
+    fn unsupported_statements_name_their_keyword() {
+        let parse = GroovyParser::parse("throw e\ntry {} finally {}");
+        let messages: Vec<&str> = parse
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "`throw` statements are not supported yet",
+                "`try` statements are not supported yet"
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_syntax_is_skipped_without_errors() {
+        let input = "def f(String args[]) {
+  try {
+    g(-1, !done)
+  }
+  catch (Exception e) {
+    throw new IllegalStateException('x', e)
+  }
+  switch (x) { case 1: break }
+  def xs = [1, 2].collect { it * 2 }
+  this.g(new ArrayList<String>() {})
+  return xs
+}";
+        let parse = GroovyParser::parse(input);
+        let messages: Vec<&str> = parse
+            .diagnostics
+            .iter()
+            .map(|diag| {
+                assert_eq!(diag.severity, Severity::Information, "{:?}", diag);
+                diag.message.as_str()
+            })
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "`try` statements are not supported yet",
+                "`switch` statements are not supported yet",
+                "lists and maps are not supported yet",
+                "closures are not supported yet",
+                "`this` expressions are not supported yet",
+                "`new` expressions are not supported yet",
+            ]
+        );
+        let method = &parse.unit.methods[0];
+        assert_eq!(
+            method.parameters[0].param_type,
+            Type::Array(Box::new(class_type("String")))
+        );
+        assert!(matches!(
+            body(method).last().unwrap().kind,
+            StmtKind::Return { expr: Some(..) }
+        ));
+    }
+
+    #[test]
+    fn valid_groovy_is_never_an_error() {
+        let inputs = [
+            "def y = a ? b : c",
+            "def y = a ?: b",
+            "def y = a?.b?.c()",
+            "list[0] = xs?[1]",
+            "def y = x instanceof String && x !instanceof Integer",
+            "def y = x as String",
+            "def y = x.&foo",
+            "def y = xs*.name",
+            "println 'hello'",
+            "println \"${x}\"",
+            "assert x == 1 : 'msg'",
+            "def (a, b) = [1, 2]",
+            "def s = \"$this.name\"",
+            "private int count = 0",
+        ];
+        for input in inputs {
+            let parse = GroovyParser::parse(input);
+            assert!(
+                parse
+                    .diagnostics
+                    .iter()
+                    .all(|diag| diag.severity != Severity::Error),
+                "{:?}: {:?}",
+                input,
+                parse.diagnostics
+            );
+        }
+
+        // what follows the unsupported part is still parsed
+        let parse = GroovyParser::parse(
+            "private int count = 0
+assert x == 1 : 'msg'",
+        );
+        match &parse.unit.statements[0].kind {
+            StmtKind::VariableDeclaration(var) => assert_eq!(var.type_name, Type::Int),
+            kind => panic!("expected declaration, got {:?}", kind),
+        }
+        assert!(matches!(
+            parse.unit.statements[1].kind,
+            StmtKind::Assert {
+                message: Some(..),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn diamond_has_no_type_arguments() {
+        let input = "class A { int f(ArrayList<> a) { return 1 } }";
+        let (class, errors) = parse_class(input);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            class.methods[0].parameters[0].param_type,
+            Type::Class {
+                qualifier: Vec::new(),
+                name: Identifier::new("ArrayList"),
+                arguments: Some(Vec::new()),
+            }
+        );
+    }
+
+    #[test]
+    fn lookahead_does_not_split_shifts() {
+        // `a<b>>c` looks like a generic type until the `c`
+        let input = "class A { int f(int a) { a<b>>c } }";
+        let parse = GroovyParser::parse(input);
+        let shifts = parse
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| token.text() == ">>")
+            .count();
+        assert_eq!(shifts, 1);
+    }
+
+    #[test]
+    fn class_headers_are_checked() {
+        let input = "class A extends B, C {}\nenum E extends A {}\ninterface I implements J {}\n";
+        let parse = GroovyParser::parse(input);
+        let messages: Vec<&str> = parse
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "a class can only extend one class",
+                "enums and records cannot extend a class",
+                "interfaces use `extends` to inherit other interfaces",
+            ]
+        );
+        assert_eq!(parse.unit.types.len(), 3);
+    }
+
+    #[test]
+    fn package_and_imports() {
+        let input = "package a.b.c\nimport a.b.C\nimport a.b.*\nimport static a.B.m\nimport static a.B.*; import a.B as Alias\nclass D {}\n";
+        let parse = GroovyParser::parse(input);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+
+        let unit = &parse.unit;
+        let package = &unit.package.as_ref().unwrap().name;
+        assert_eq!(package.to_string(), "a.b.c");
+        let segments: Vec<Span> = package
+            .segments
+            .iter()
+            .map(|segment| segment.span)
+            .collect();
+        assert_eq!(
+            segments,
+            [Span::new(8, 9), Span::new(10, 11), Span::new(12, 13)]
+        );
+
+        let imports: Vec<(String, bool, bool, Option<&str>)> = unit
+            .imports
+            .iter()
+            .map(|import| {
+                (
+                    import.name.to_string(),
+                    import.is_static,
+                    import.is_star,
+                    import.alias.map(|alias| text(input, alias.span)),
+                )
+            })
+            .collect();
+        assert_eq!(
+            imports,
+            [
+                ("a.b.C".to_owned(), false, false, None),
+                ("a.b".to_owned(), false, true, None),
+                ("a.B.m".to_owned(), true, false, None),
+                ("a.B".to_owned(), true, true, None),
+                ("a.B".to_owned(), false, false, Some("Alias")),
+            ]
+        );
+        assert_eq!(unit.types.len(), 1);
+
+        let file = parse.source_file();
+        let package: Vec<String> = file
+            .package()
+            .and_then(|package| package.name())
+            .unwrap()
+            .segments()
+            .map(|segment| segment.text().to_owned())
+            .collect();
+        assert_eq!(package, ["a", "b", "c"]);
+        let alias = file.imports().last().unwrap().alias().unwrap();
+        assert_eq!(alias.text(), "Alias");
+
+        let parse = GroovyParser::parse("package a\npackage b\nimport a.\nimport b.C");
+        let messages: Vec<&str> = parse
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "a file can only declare one package",
+                "expected `*`, found keyword `import`"
+            ]
+        );
+        assert_eq!(parse.unit.imports.len(), 1);
+    }
+
+    #[test]
+    fn recovers_from_malformed_imports() {
+        let input = "import a.B as\nimport static\nimport c.D\nimport e.F as G\nclass E {}";
+        let parse = GroovyParser::parse(input);
+        let messages: Vec<&str> = parse
+            .diagnostics
+            .iter()
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "expected identifier, found line break",
+                "expected identifier, found line break"
+            ]
+        );
+        let imports: Vec<(String, Option<&str>)> = parse
+            .unit
+            .imports
+            .iter()
+            .map(|import| {
+                (
+                    import.name.to_string(),
+                    import.alias.map(|alias| text(input, alias.span)),
+                )
+            })
+            .collect();
+        assert_eq!(
+            imports,
+            [("c.D".to_owned(), None), ("e.F".to_owned(), Some("G"))]
+        );
+        assert_eq!(parse.unit.types.len(), 1);
+    }
+
+    #[test]
+    fn unterminated_input_never_panics() {
+        let input =
+            "public class A {\n    int f(int x, long y) {\n        def z = 1 * 2;\n        \
+                     assert z, 3\n        return \"${z / \"$z.a${'}'}\"} $z\" / 4\n    }\n}\nclass B { }";
+        for (idx, _) in input.char_indices() {
+            GroovyParser::parse(&input[..idx]);
+        }
+        let (_, errors) = parse_class("class A { void f(int a) { return 1");
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+    }
+
+    #[test]
+    fn diagnostics_describe_what_was_found() {
+        let input = "class 1 {}\nclass B { void f(int a) { # }";
+        let Parse { diagnostics, .. } = GroovyParser::parse(input);
+        let messages: Vec<(ErrorCode, &str)> = diagnostics
+            .iter()
+            .map(|diag| (diag.code, diag.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (ErrorCode::UnknownCharacter, "unknown character `#`"),
+                (
+                    ErrorCode::UnexpectedToken,
+                    "expected identifier, found number `1`"
+                ),
+                (
+                    ErrorCode::UnclosedDelimiter,
+                    "expected `}`, found end of file"
+                ),
+            ]
+        );
+
+        let unclosed = &diagnostics[2];
+        assert_eq!(unclosed.span, Span::empty(input.len()));
+        assert_eq!(text(input, unclosed.labels[0].span), "{");
+        assert_eq!(
+            unclosed.labels[0].span.start,
+            input.find("B {").unwrap() + 2
+        );
+        assert_eq!(unclosed.suggestions[0].replacement, "}");
+    }
+
+    #[test]
+    fn statements_end_at_line_breaks() {
+        let input = "class A\n{\n  int f(int a)\n  {\n    int x = 1 +\n      2\n    x\n      .y\n\n    return x\n  }\n}\n";
+        let (class, errors) = parse_class(input);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let body = body(&class.methods[0]);
+        assert_eq!(body.len(), 3);
+        assert_eq!(text(input, body[0].span), "int x = 1 +\n      2");
+        assert_eq!(text(input, body[1].span), "x\n      .y");
+        assert!(matches!(body[2].kind, StmtKind::Return { .. }));
+
+        let (_, errors) = parse_class("class A { int f(int a) { return 1 return 2 } }");
+        assert_eq!(
+            errors[0].message,
+            "expected `;` or a line break, found keyword `return`"
+        );
+    }
+
+    #[test]
+    fn gstring_interpolations_are_expressions() {
+        let input = r#"class A { void f(int a) { return "v=${a + 1} $a.b ${ oops oops }!" } }"#;
+        let (class, errors) = parse_class(input);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+
+        let parts = match &body(&class.methods[0])[0].kind {
+            StmtKind::Return { expr: Some(expr) } => match &expr.kind {
+                ExprKind::GString(GString(parts)) => parts,
+                kind => panic!("expected gstring, got {:?}", kind),
+            },
+            kind => panic!("expected return, got {:?}", kind),
+        };
+        let exprs: Vec<&Expr> = parts
+            .iter()
+            .filter_map(|part| match part {
+                InterpolatedStringPart::Expression(expr) => Some(&**expr),
+                InterpolatedStringPart::Literal(..) => None,
+            })
+            .collect();
+        assert_eq!(exprs.len(), 3);
+
+        assert!(matches!(exprs[0].kind, ExprKind::BinaryOp { .. }));
+        assert_eq!(text(input, exprs[0].span), "a + 1");
+        match &exprs[1].kind {
+            ExprKind::Property { object, property } => {
+                assert!(matches!(object.kind, ExprKind::Variable { .. }));
+                assert_eq!(text(input, property.span), "b");
+            }
+            kind => panic!("expected property, got {:?}", kind),
+        }
+        assert_eq!(text(input, exprs[1].span), "a.b");
+        // the error stays inside its interpolation
+        assert!(matches!(exprs[2].kind, ExprKind::Error));
+        assert_eq!(
+            parts.last(),
+            Some(&InterpolatedStringPart::Literal("!".to_owned()))
+        );
+    }
+
+    #[test]
+    fn method_calls_in_interpolations() {
+        let input = r#"class A { def f(List x) { return "${x.size()} ${g(x, 1)} $x.size()" } }"#;
+        let (class, errors) = parse_class(input);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let parts = match &body(&class.methods[0])[0].kind {
+            StmtKind::Return { expr: Some(expr) } => match &expr.kind {
+                ExprKind::GString(GString(parts)) => parts,
+                kind => panic!("expected gstring, got {:?}", kind),
+            },
+            kind => panic!("expected return, got {:?}", kind),
+        };
+        let exprs: Vec<&Expr> = parts
+            .iter()
+            .filter_map(|part| match part {
+                InterpolatedStringPart::Expression(expr) => Some(&**expr),
+                InterpolatedStringPart::Literal(..) => None,
+            })
+            .collect();
+        assert_eq!(exprs.len(), 3);
+
+        match &exprs[0].kind {
+            ExprKind::MethodCall {
+                object: Some(object),
+                method,
+                arguments,
+                ..
+            } => {
+                assert!(matches!(object.kind, ExprKind::Variable { .. }));
+                assert_eq!(text(input, method.span), "size");
+                assert!(arguments.is_empty());
+            }
+            kind => panic!("expected method call, got {:?}", kind),
+        }
+        assert_eq!(text(input, exprs[0].span), "x.size()");
+        match &exprs[1].kind {
+            ExprKind::MethodCall {
+                object: None,
+                method,
+                arguments,
+                ..
+            } => {
+                assert_eq!(text(input, method.span), "g");
+                assert_eq!(arguments.len(), 2);
+            }
+            kind => panic!("expected method call, got {:?}", kind),
+        }
+        // as in groovyc, a `$path` ends before the parentheses
+        assert!(matches!(exprs[2].kind, ExprKind::Property { .. }));
+        assert_eq!(
+            parts.last(),
+            Some(&InterpolatedStringPart::Literal("()".to_owned()))
+        );
+    }
+
+    #[test]
+    fn groovydoc_is_attached_to_declarations() {
+        let input = "/** A class */\npublic class A {\n  /**\n   * Does f.\n   */\n  static int f(int a) { return 1 }\n  /** detached */ ;\n  int g(int a) { return 2 }\n}";
+        let (class, errors) = parse_class(input);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(class.doc.as_deref(), Some("A class"));
+        assert_eq!(class.methods[0].doc.as_deref(), Some("Does f."));
+        assert_eq!(class.methods[1].doc, None);
+    }
+}
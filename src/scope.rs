@@ -0,0 +1,236 @@
+//! The local variables visible at each point of a file
+
+use crate::{
+    ast::{Class, Identifier, Method, Stmt, StmtKind},
+    parse::Parse,
+    span::Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScopeId(u32);
+
+/// A local variable or parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeEntry {
+    pub name: Identifier,
+    /// The span of the name where it is declared
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeData {
+    pub parent: Option<ScopeId>,
+    /// The region of the file in which the entries are visible
+    pub span: Span,
+    /// Entries in the order they are declared
+    pub entries: Vec<ScopeEntry>,
+}
+
+/// Every scope in a file. The statements of a script share a scope covering
+/// the whole file, each method introduces a scope holding its parameters, as
+/// does each initializer block, and each block nested inside any of these
+/// introduces another
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scopes {
+    scopes: Vec<ScopeData>,
+}
+
+impl Scopes {
+    pub fn new(parse: &Parse) -> Self {
+        let mut scopes = Self::default();
+        let unit = &parse.unit;
+        if !unit.statements.is_empty() {
+            let script = scopes.alloc(None, unit.span);
+            for stmt in &unit.statements {
+                scopes.add_stmt(script, stmt);
+            }
+        }
+        for class in &unit.types {
+            scopes.add_class(class);
+        }
+        for method in &unit.methods {
+            scopes.add_method(method);
+        }
+        scopes
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ScopeId, &ScopeData)> {
+        self.scopes
+            .iter()
+            .enumerate()
+            .map(|(i, data)| (ScopeId(i as u32), data))
+    }
+
+    pub fn get(&self, scope: ScopeId) -> &ScopeData {
+        &self.scopes[scope.0 as usize]
+    }
+
+    /// The scope itself followed by each of its parents in turn
+    pub fn ancestors(&self, scope: ScopeId) -> impl Iterator<Item = ScopeId> + '_ {
+        std::iter::successors(Some(scope), move |&scope| self.get(scope).parent)
+    }
+
+    /// The innermost scope containing `offset`
+    pub fn scope_at(&self, offset: usize) -> Option<ScopeId> {
+        // children are always allocated after their parents, so the last
+        // match is the innermost
+        self.iter()
+            .filter(|(_, data)| data.span.contains(offset))
+            .map(|(id, _)| id)
+            .last()
+    }
+
+    /// Finds the declaration of `name` visible at `offset`
+    pub fn resolve(&self, offset: usize, name: Identifier) -> Option<&ScopeEntry> {
+        let scope = self.scope_at(offset)?;
+        self.ancestors(scope).find_map(|scope| {
+            self.get(scope)
+                .entries
+                .iter()
+                .rev()
+                .find(|entry| entry.name == name && entry.span.start < offset)
+        })
+    }
+
+    fn alloc(&mut self, parent: Option<ScopeId>, span: Span) -> ScopeId {
+        let id = ScopeId(self.scopes.len() as u32);
+        self.scopes.push(ScopeData {
+            parent,
+            span,
+            entries: Vec::new(),
+        });
+        id
+    }
+
+    fn add_class(&mut self, class: &Class) {
+        for method in &class.methods {
+            self.add_method(method);
+        }
+        for initializer in &class.initializers {
+            let scope = self.alloc(None, initializer.span);
+            self.add_stmt(scope, &initializer.body);
+        }
+        for constant in &class.enum_constants {
+            if let Some(body) = &constant.body {
+                self.add_class(body);
+            }
+        }
+    }
+
+    fn add_method(&mut self, method: &Method) {
+        let scope = self.alloc(None, method.span);
+        for param in &method.parameters {
+            self.scopes[scope.0 as usize].entries.push(ScopeEntry {
+                name: param.name.node,
+                span: param.name.span,
+            });
+        }
+        if let Some(body) = &method.body {
+            self.add_stmt(scope, body);
+        }
+    }
+
+    fn add_stmt(&mut self, scope: ScopeId, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Block { body, .. } => {
+                let block = self.alloc(Some(scope), stmt.span);
+                for stmt in body {
+                    self.add_stmt(block, stmt);
+                }
+            }
+            StmtKind::VariableDeclaration(var) => {
+                self.scopes[scope.0 as usize].entries.push(ScopeEntry {
+                    name: var.name.node,
+                    span: var.name.span,
+                });
+            }
+            StmtKind::If {
+                if_block,
+                else_block,
+                ..
+            } => {
+                self.add_stmt(scope, if_block);
+                if let Some(else_block) = else_block {
+                    self.add_stmt(scope, else_block);
+                }
+            }
+            StmtKind::For {
+                variable,
+                loop_block,
+                ..
+            } => {
+                let for_scope = self.alloc(Some(scope), stmt.span);
+                self.scopes[for_scope.0 as usize].entries.push(ScopeEntry {
+                    name: variable.name.node,
+                    span: variable.name.span,
+                });
+                self.add_stmt(for_scope, loop_block);
+            }
+            StmtKind::ClassicFor {
+                init, loop_block, ..
+            } => {
+                let for_scope = self.alloc(Some(scope), stmt.span);
+                for stmt in init {
+                    self.add_stmt(for_scope, stmt);
+                }
+                self.add_stmt(for_scope, loop_block);
+            }
+            StmtKind::While { loop_block, .. } | StmtKind::DoWhile { loop_block, .. } => {
+                self.add_stmt(scope, loop_block);
+            }
+            StmtKind::Labeled { stmt, .. } => self.add_stmt(scope, stmt),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::GroovyParser;
+
+    #[test]
+    fn resolves_params_and_locals() {
+        let input = "class A {\n  int f(int a) {\n    int b = 1\n    {\n      int c = 2\n    }\n    return 3\n  }\n}\n";
+        let parse = GroovyParser::parse(input);
+        let scopes = Scopes::new(&parse);
+        let at = |needle: &str| input.find(needle).unwrap();
+
+        let a = scopes.resolve(at("= 1"), Identifier::new("a")).unwrap();
+        assert_eq!(a.span, Span::new(at("a)"), at("a)") + 1));
+
+        let b = scopes.resolve(at("= 2"), Identifier::new("b")).unwrap();
+        assert_eq!(b.span.start, at("b ="));
+
+        // `c` is only visible inside the inner block
+        assert!(scopes.resolve(at("= 2"), Identifier::new("c")).is_some());
+        assert!(scopes.resolve(at("return"), Identifier::new("c")).is_none());
+        // and nothing is visible before it is declared
+        assert!(scopes.resolve(at("int b"), Identifier::new("b")).is_none());
+    }
+
+    #[test]
+    fn script_locals_are_not_visible_in_methods() {
+        let input = "def a = 1\na + 2\nint f(int b) {\n  return a\n}\n";
+        let scopes = Scopes::new(&GroovyParser::parse(input));
+        let at = |needle: &str| input.find(needle).unwrap();
+
+        assert!(scopes.resolve(at("+ 2"), Identifier::new("a")).is_some());
+        assert!(scopes.resolve(at("return"), Identifier::new("a")).is_none());
+        assert!(scopes.resolve(at("return"), Identifier::new("b")).is_some());
+    }
+
+    #[test]
+    fn loop_variables_are_scoped_to_their_loops() {
+        let input = "for (int i = 0; i < 3; i++) {\n  i + 1\n}\nfor (x in xs) {\n  x + 1\n}\nwhile (true) {\n  int y = 1\n}\ndone()";
+        let scopes = Scopes::new(&GroovyParser::parse(input));
+        let at = |needle: &str| input.find(needle).unwrap();
+
+        let i = scopes.resolve(at("i + 1"), Identifier::new("i")).unwrap();
+        assert_eq!(i.span.start, at("i = 0"));
+        assert!(scopes.resolve(at("x + 1"), Identifier::new("x")).is_some());
+        assert!(scopes.resolve(at("for (x"), Identifier::new("i")).is_none());
+        assert!(scopes.resolve(at("while"), Identifier::new("x")).is_none());
+        assert!(scopes.resolve(at("done"), Identifier::new("y")).is_none());
+    }
+}
@@ -0,0 +1,287 @@
+//! Byte-offset source locations and the conversion from those offsets to the
+//! line/column positions used by editors.
+
+use std::cmp::{max, min};
+
+/// A half-open range of byte offsets into the source text, `start..end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub const fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// An empty span positioned at `offset`
+    pub const fn empty(offset: usize) -> Self {
+        Self::new(offset, offset)
+    }
+
+    pub const fn len(self) -> usize {
+        self.end - self.start
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+
+    /// The smallest span covering both `self` and `other`
+    pub fn to(self, other: Span) -> Span {
+        Span::new(min(self.start, other.start), max(self.end, other.end))
+    }
+
+    /// Whether `offset` falls within this span. The end offset is treated as
+    /// inclusive so that a cursor placed directly after a token still counts
+    pub const fn contains(self, offset: usize) -> bool {
+        self.start <= offset && offset <= self.end
+    }
+}
+
+/// An AST value paired with the source range it was parsed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub const fn new(node: T, span: Span) -> Self {
+        Self { node, span }
+    }
+}
+
+/// A zero-based line and column. The column is measured in UTF-16 code units,
+/// as required by the Language Server Protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+/// A character which is encoded with more than one byte in UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WideChar {
+    /// Byte offset of the character relative to the start of its line
+    start: usize,
+    len_utf8: usize,
+    len_utf16: usize,
+}
+
+/// Maps byte offsets to line/column positions and back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// Byte offset of the first character of every line
+    line_starts: Vec<usize>,
+    /// The multi-byte characters of each line, indexed by line number
+    wide_chars: Vec<Vec<WideChar>>,
+    /// Whether each line ends with `\r`, so that its line break is `\r\n`
+    crlf: Vec<bool>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let (starts, wide_chars, crlf) = Self::scan(text, 0);
+        let mut line_starts = vec![0];
+        line_starts.extend(starts);
+        Self {
+            line_starts,
+            wide_chars,
+            crlf,
+            len: text.len(),
+        }
+    }
+
+    /// Finds the lines of `text`, which begins at offset `base` of a line.
+    /// Returns the start of every line after the first, and the multi-byte
+    /// characters and trailing `\r` of every line
+    fn scan(text: &str, base: usize) -> (Vec<usize>, Vec<Vec<WideChar>>, Vec<bool>) {
+        let mut line_starts = Vec::new();
+        let mut wide_chars = vec![Vec::new()];
+        let mut crlf = Vec::new();
+        let mut line_start = 0;
+
+        for (offset, c) in text.char_indices() {
+            if c == '\n' {
+                crlf.push(text[..offset].ends_with('\r'));
+                line_start = offset + 1;
+                line_starts.push(base + line_start);
+                wide_chars.push(Vec::new());
+            } else if !c.is_ascii() {
+                wide_chars.last_mut().unwrap().push(WideChar {
+                    start: offset - line_start,
+                    len_utf8: c.len_utf8(),
+                    len_utf16: c.len_utf16(),
+                });
+            }
+        }
+
+        crlf.push(text.ends_with('\r'));
+        (line_starts, wide_chars, crlf)
+    }
+
+    /// Updates the index after `span` of the old text was replaced by
+    /// `new_len` bytes, given the new `text`. Only the lines touched by the
+    /// edit are scanned again; the lines after it are just shifted
+    pub fn apply_edit(&mut self, text: &str, span: Span, new_len: usize) {
+        let line_of = |offset| match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let first = line_of(span.start);
+        let last = line_of(span.end);
+
+        let start = self.line_starts[first];
+        let new_end = span.start + new_len;
+        let end = match text[new_end..].find('\n') {
+            Some(newline) => new_end + newline,
+            None => text.len(),
+        };
+        let (starts, wide_chars, crlf) = Self::scan(&text[start..end], start);
+
+        let after = first + 1 + starts.len();
+        self.line_starts.splice(first + 1..=last, starts);
+        self.wide_chars.splice(first..=last, wide_chars);
+        self.crlf.splice(first..=last, crlf);
+        for line_start in &mut self.line_starts[after..] {
+            *line_start = *line_start + new_len - span.len();
+        }
+        self.len = text.len();
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Converts a byte offset into a line and UTF-16 column. Offsets past the
+    /// end of the text are clamped to the end
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let offset = min(offset, self.len);
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let col_utf8 = offset - self.line_starts[line];
+
+        let mut col = col_utf8;
+        for c in &self.wide_chars[line] {
+            if c.start >= col_utf8 {
+                break;
+            }
+            col = col - c.len_utf8 + c.len_utf16;
+        }
+
+        LineCol {
+            line: line as u32,
+            col: col as u32,
+        }
+    }
+
+    /// Converts a line and UTF-16 column back into a byte offset. Returns
+    /// `None` if the line does not exist; columns past the end of a line are
+    /// clamped to the end of that line, before its `\r\n` or `\n`
+    pub fn offset(&self, line_col: LineCol) -> Option<usize> {
+        let line = line_col.line as usize;
+        let line_start = *self.line_starts.get(line)?;
+        let line_end = match self.line_starts.get(line + 1) {
+            Some(next) => next - 1 - usize::from(self.crlf[line]),
+            None => self.len,
+        };
+
+        let mut remaining = line_col.col as usize;
+        let mut col_utf8 = 0;
+        for c in &self.wide_chars[line] {
+            let ascii_run = c.start - col_utf8;
+            if remaining <= ascii_run {
+                break;
+            }
+            remaining -= ascii_run;
+            col_utf8 = c.start;
+            if remaining < c.len_utf16 {
+                // the position points into the middle of a surrogate pair
+                return Some(line_start + col_utf8);
+            }
+            remaining -= c.len_utf16;
+            col_utf8 += c.len_utf8;
+        }
+
+        Some(min(line_start + col_utf8 + remaining, line_end))
+    }
+
+    /// Converts both ends of a span into line/column positions
+    pub fn range(&self, span: Span) -> (LineCol, LineCol) {
+        (self.line_col(span.start), self.line_col(span.end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lc(line: u32, col: u32) -> LineCol {
+        LineCol { line, col }
+    }
+
+    #[test]
+    fn ascii_positions() {
+        let index = LineIndex::new("class A {\n  int x\n}");
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(index.line_col(0), lc(0, 0));
+        assert_eq!(index.line_col(9), lc(0, 9));
+        assert_eq!(index.line_col(10), lc(1, 0));
+        assert_eq!(index.line_col(16), lc(1, 6));
+        assert_eq!(index.line_col(100), lc(2, 1));
+        assert_eq!(index.offset(lc(1, 2)), Some(12));
+        assert_eq!(index.offset(lc(1, 50)), Some(17));
+        assert_eq!(index.offset(lc(3, 0)), None);
+    }
+
+    #[test]
+    fn utf16_columns() {
+        // `é` is 2 bytes/1 unit, `𝄞` is 4 bytes/2 units
+        let text = "def é = '𝄞x'\nfoo";
+        let index = LineIndex::new(text);
+        let x = text.find('x').unwrap();
+        assert_eq!(index.line_col(x), lc(0, 11));
+        assert_eq!(index.offset(lc(0, 11)), Some(x));
+        let eq = text.find('=').unwrap();
+        assert_eq!(index.line_col(eq), lc(0, 6));
+        assert_eq!(index.offset(lc(0, 6)), Some(eq));
+        assert_eq!(index.line_col(text.len()), lc(1, 3));
+    }
+
+    #[test]
+    fn edits_update_the_index() {
+        let mut text = "class A {\n  int x\n}\ndef é = '𝄞'\n".to_owned();
+        let mut index = LineIndex::new(&text);
+        // each edit replaces the first occurrence of a piece of the text
+        let edits = [
+            ("int", "String"),
+            ("", "𝄞"),
+            ("{\n", "{\n  // é\n\n"),
+            ("é\n\n  String x\n}", "}"),
+            ("'𝄞'\n", "x\ny"),
+            ("}\nd", "d"),
+        ];
+        for (old, new) in edits {
+            let start = text.find(old).unwrap();
+            let span = Span::new(start, start + old.len());
+            text.replace_range(span.start..span.end, new);
+            index.apply_edit(&text, span, new.len());
+            assert_eq!(index, LineIndex::new(&text), "after inserting {:?}", new);
+        }
+    }
+
+    #[test]
+    fn span_merging() {
+        let a = Span::new(3, 5);
+        let b = Span::new(10, 12);
+        assert_eq!(a.to(b), Span::new(3, 12));
+        assert_eq!(b.to(a), Span::new(3, 12));
+        assert!(a.contains(5));
+        assert!(!a.contains(6));
+    }
+}
@@ -0,0 +1,510 @@
+//! The lossless concrete syntax tree. Unlike the structs in `ast`, it keeps
+//! every byte of the source, including whitespace and comments, so the text of
+//! a file can be reproduced exactly and edits can be made by rewriting the
+//! tree. The parser records the shape of the tree as a flat list of events,
+//! which is turned into a tree once parsing has finished
+
+mod nodes;
+
+use rowan::{GreenNode, GreenNodeBuilder, GreenToken};
+
+use crate::ast::{Literal, SpannedToken, Token};
+
+pub use nodes::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum SyntaxKind {
+    // trivia
+    Whitespace,
+    /// Line, block and Groovydoc comments, and the shebang line
+    Comment,
+    /// Text which the lexer could not make sense of
+    Unknown,
+
+    // tokens
+    NewLine,
+    Semicolon,
+    Ident,
+    /// Any keyword, including `true`, `false` and `null`
+    Keyword,
+    Number,
+    String,
+    ParenOpen,
+    ParenClose,
+    CurlyBraceOpen,
+    CurlyBraceClose,
+    SquareBraceOpen,
+    SquareBraceClose,
+    Comma,
+    Period,
+    SingleEqual,
+    AtSign,
+    Colon,
+    /// Any other operator
+    Operator,
+    GStringStart,
+    GStringPart,
+    GStringEnd,
+    Dollar,
+    /// `${`
+    InterpolationStart,
+
+    // nodes
+    SourceFile,
+    PackageDecl,
+    ImportDecl,
+    QualifiedName,
+    ClassDecl,
+    ExtendsClause,
+    ImplementsClause,
+    PermitsClause,
+    EnumConstant,
+    FieldDecl,
+    Declarator,
+    Initializer,
+    MethodDecl,
+    ParamList,
+    Param,
+    ThrowsClause,
+    Annotation,
+    /// A single argument of an annotation, `key = value`
+    AnnotationElement,
+    /// A list of values given to an annotation element, `['a', 'b']`
+    AnnotationArray,
+    /// The `default value` of a member of an annotation type
+    AnnotationDefault,
+    TypeRef,
+    TypeArgList,
+    TypeParamList,
+    TypeParam,
+    ArgList,
+    Block,
+    VarDecl,
+    ReturnStmt,
+    AssertStmt,
+    ExprStmt,
+    IfStmt,
+    WhileStmt,
+    DoWhileStmt,
+    ForStmt,
+    BreakStmt,
+    ContinueStmt,
+    /// A statement preceded by `label:`
+    LabeledStmt,
+    /// The declarations or expressions before the first `;` of a classic
+    /// `for` loop
+    ForInit,
+    /// The expressions after the second `;` of a classic `for` loop
+    ForUpdate,
+    BinExpr,
+    AssignExpr,
+    PrefixExpr,
+    PostfixExpr,
+    LiteralExpr,
+    NameRef,
+    ParenExpr,
+    PropertyExpr,
+    /// A method call, wrapping the called name or property and its `ArgList`
+    CallExpr,
+    GStringExpr,
+    /// Tokens which could not be parsed, or an empty node where something was
+    /// missing
+    Error,
+}
+
+impl SyntaxKind {
+    /// Every kind, indexed by its raw value. Checked at compile time to
+    /// match the order of the variants
+    const ALL: [SyntaxKind; SyntaxKind::Error as usize + 1] = {
+        use SyntaxKind::*;
+        let all = [
+            Whitespace,
+            Comment,
+            Unknown,
+            NewLine,
+            Semicolon,
+            Ident,
+            Keyword,
+            Number,
+            String,
+            ParenOpen,
+            ParenClose,
+            CurlyBraceOpen,
+            CurlyBraceClose,
+            SquareBraceOpen,
+            SquareBraceClose,
+            Comma,
+            Period,
+            SingleEqual,
+            AtSign,
+            Colon,
+            Operator,
+            GStringStart,
+            GStringPart,
+            GStringEnd,
+            Dollar,
+            InterpolationStart,
+            SourceFile,
+            PackageDecl,
+            ImportDecl,
+            QualifiedName,
+            ClassDecl,
+            ExtendsClause,
+            ImplementsClause,
+            PermitsClause,
+            EnumConstant,
+            FieldDecl,
+            Declarator,
+            Initializer,
+            MethodDecl,
+            ParamList,
+            Param,
+            ThrowsClause,
+            Annotation,
+            AnnotationElement,
+            AnnotationArray,
+            AnnotationDefault,
+            TypeRef,
+            TypeArgList,
+            TypeParamList,
+            TypeParam,
+            ArgList,
+            Block,
+            VarDecl,
+            ReturnStmt,
+            AssertStmt,
+            ExprStmt,
+            IfStmt,
+            WhileStmt,
+            DoWhileStmt,
+            ForStmt,
+            BreakStmt,
+            ContinueStmt,
+            LabeledStmt,
+            ForInit,
+            ForUpdate,
+            BinExpr,
+            AssignExpr,
+            PrefixExpr,
+            PostfixExpr,
+            LiteralExpr,
+            NameRef,
+            ParenExpr,
+            PropertyExpr,
+            CallExpr,
+            GStringExpr,
+            Error,
+        ];
+        let mut i = 0;
+        while i < all.len() {
+            assert!(all[i] as usize == i, "kinds are listed out of order");
+            i += 1;
+        }
+        all
+    };
+
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::Whitespace | SyntaxKind::Comment | SyntaxKind::Unknown
+        )
+    }
+}
+
+impl From<SyntaxKind> for rowan::SyntaxKind {
+    fn from(kind: SyntaxKind) -> Self {
+        Self(kind as u16)
+    }
+}
+
+impl From<&Token> for SyntaxKind {
+    fn from(token: &Token) -> Self {
+        match token {
+            Token::Identifier(..) => SyntaxKind::Ident,
+            Token::Keyword(..) | Token::Literal(Literal::True | Literal::False | Literal::Null) => {
+                SyntaxKind::Keyword
+            }
+            Token::Literal(Literal::Integer(..) | Literal::Decimal(..)) => SyntaxKind::Number,
+            Token::Literal(Literal::String(..)) => SyntaxKind::String,
+            Token::ExprEnd => SyntaxKind::Semicolon,
+            Token::NewLine => SyntaxKind::NewLine,
+            Token::ParenOpen => SyntaxKind::ParenOpen,
+            Token::ParenClose => SyntaxKind::ParenClose,
+            Token::CurlyBraceOpen => SyntaxKind::CurlyBraceOpen,
+            Token::CurlyBraceClose => SyntaxKind::CurlyBraceClose,
+            Token::SquareBraceOpen => SyntaxKind::SquareBraceOpen,
+            Token::SquareBraceClose => SyntaxKind::SquareBraceClose,
+            Token::Comma => SyntaxKind::Comma,
+            Token::Period => SyntaxKind::Period,
+            Token::SingleEqual => SyntaxKind::SingleEqual,
+            Token::AtSign => SyntaxKind::AtSign,
+            Token::Colon => SyntaxKind::Colon,
+            Token::GStringStart => SyntaxKind::GStringStart,
+            Token::GStringPart(..) => SyntaxKind::GStringPart,
+            Token::GStringEnd => SyntaxKind::GStringEnd,
+            Token::Dollar => SyntaxKind::Dollar,
+            Token::GStringInterpolationStart => SyntaxKind::InterpolationStart,
+            _ => SyntaxKind::Operator,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GroovyLanguage {}
+
+impl rowan::Language for GroovyLanguage {
+    type Kind = SyntaxKind;
+
+    fn kind_from_raw(raw: rowan::SyntaxKind) -> SyntaxKind {
+        SyntaxKind::ALL[usize::from(raw.0)]
+    }
+
+    fn kind_to_raw(kind: SyntaxKind) -> rowan::SyntaxKind {
+        kind.into()
+    }
+}
+
+pub type SyntaxNode = rowan::SyntaxNode<GroovyLanguage>;
+pub type SyntaxToken = rowan::SyntaxToken<GroovyLanguage>;
+pub type SyntaxElement = rowan::SyntaxElement<GroovyLanguage>;
+
+/// Replaces the text of `token`, returning the root of the rewritten tree. The
+/// tree containing `token` is left unchanged
+pub fn replace_token(token: &SyntaxToken, text: &str) -> SyntaxNode {
+    SyntaxNode::new_root(token.replace_with(GreenToken::new(token.kind().into(), text)))
+}
+
+/// One step in building the tree, as recorded by the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Event {
+    /// The start of a node. `kind` is `None` for a checkpoint at which no
+    /// node was started, and `forward_parent` is the distance to the start of
+    /// a node which was later started at the same position and wraps this one
+    Start {
+        kind: Option<SyntaxKind>,
+        forward_parent: Option<usize>,
+    },
+    /// The next token of the input
+    Token,
+    /// The next token of the input, an identifier which is used as a
+    /// contextual keyword
+    ContextualKeyword,
+    Finish,
+}
+
+/// Builds the tree of `input` from the events recorded while parsing
+/// `tokens`. Whitespace and comments become trivia, which is placed outside
+/// of any node beginning after it. Tokens which the parser never consumed are
+/// attached to the root, so that no text is lost
+pub(crate) fn build_tree(
+    input: &str,
+    tokens: &[SpannedToken],
+    mut events: Vec<Event>,
+) -> GreenNode {
+    let mut builder = TreeBuilder {
+        input,
+        tokens,
+        next: 0,
+        offset: 0,
+        builder: GreenNodeBuilder::new(),
+    };
+    builder.builder.start_node(SyntaxKind::SourceFile.into());
+    let placeholder = Event::Start {
+        kind: None,
+        forward_parent: None,
+    };
+    let mut kinds = Vec::new();
+    for idx in 0..events.len() {
+        match std::mem::replace(&mut events[idx], placeholder) {
+            Event::Start {
+                kind,
+                mut forward_parent,
+            } => {
+                // the outermost of the nodes starting here comes last
+                kinds.push(kind);
+                let mut parent = idx;
+                while let Some(distance) = forward_parent {
+                    parent += distance;
+                    let Event::Start {
+                        kind,
+                        forward_parent: next,
+                    } = std::mem::replace(&mut events[parent], placeholder)
+                    else {
+                        unreachable!("a forward parent is always the start of a node");
+                    };
+                    kinds.push(kind);
+                    forward_parent = next;
+                }
+                for kind in kinds.drain(..).rev().flatten() {
+                    builder.trivia(builder.next_token_start());
+                    builder.builder.start_node(kind.into());
+                }
+            }
+            Event::Token => builder.token(None),
+            Event::ContextualKeyword => builder.token(Some(SyntaxKind::Keyword)),
+            Event::Finish => builder.builder.finish_node(),
+        }
+    }
+    while builder.next < tokens.len() {
+        builder.token(None);
+    }
+    builder.trivia(input.len());
+    builder.builder.finish_node();
+    builder.builder.finish()
+}
+
+struct TreeBuilder<'a> {
+    input: &'a str,
+    tokens: &'a [SpannedToken],
+    /// Index of the next token to be added
+    next: usize,
+    /// The end of the text added so far
+    offset: usize,
+    builder: GreenNodeBuilder<'static>,
+}
+
+impl TreeBuilder<'_> {
+    fn next_token_start(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.input.len(), |tok| tok.span.start)
+    }
+
+    /// Adds the next token, as `kind` if given
+    fn token(&mut self, kind: Option<SyntaxKind>) {
+        let Some(tok) = self.tokens.get(self.next) else {
+            return;
+        };
+        self.next += 1;
+        self.trivia(tok.span.start);
+        let end = tok.span.end.max(self.offset);
+        self.builder.token(
+            kind.unwrap_or_else(|| SyntaxKind::from(&tok.token)).into(),
+            &self.input[self.offset..end],
+        );
+        self.offset = end;
+    }
+
+    /// Adds the text between the last token and `end` as trivia
+    fn trivia(&mut self, end: usize) {
+        while self.offset < end {
+            let rest = &self.input[self.offset..end];
+            let (kind, len) = if rest.starts_with(|c: char| c.is_ascii_whitespace()) {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_whitespace())
+                    .unwrap_or(rest.len());
+                (SyntaxKind::Whitespace, len)
+            } else if rest.starts_with("//") || (self.offset == 0 && rest.starts_with("#!")) {
+                (SyntaxKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                let len = comment.find("*/").map_or(rest.len(), |end| end + 4);
+                (SyntaxKind::Comment, len)
+            } else {
+                let first = rest.chars().next().map_or(1, char::len_utf8);
+                let len = rest[first..]
+                    .find(|c: char| c.is_ascii_whitespace() || c == '/')
+                    .map_or(rest.len(), |len| len + first);
+                (SyntaxKind::Unknown, len)
+            };
+            self.builder.token(kind.into(), &rest[..len]);
+            self.offset += len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::GroovyParser;
+
+    #[test]
+    fn source_is_reproduced_exactly() {
+        for input in [
+            "",
+            "#!/usr/bin/env groovy\n// comment\n/** doc */\nclass A {\n\tint f(int a) {\n\t\treturn a + 1 // one\n\t}\n}\n",
+            "class A { void f(String s) { def x = \"a${s.length}b $s.c\" ; assert x, 'y' } }  ",
+            "} class # A {\n int 5\n void f(int a) { int x = ; foo bar baz }\n}\n% class B {} /* unterminated",
+            "class A { void f() { '''unterminated",
+            "class A {\r\n  int f(int a) {\r\n    x\r\n      .y ; /re/ + 1.5e3 \\\n  }\r\n}",
+        ] {
+            let parse = GroovyParser::parse(input);
+            assert_eq!(parse.syntax().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn typed_nodes() {
+        let input = "// leading\nclass A {\n  int f(int a, long b) {\n    int x = a * (b + 1)\n    return x.y\n  }\n}\n";
+        let file = GroovyParser::parse(input).source_file();
+        let class = file.classes().next().unwrap();
+        assert_eq!(class.name().unwrap().text(), "A");
+        // trivia before a node is not part of it
+        assert_eq!(class.syntax().text_range().start(), 11.into());
+
+        let method = class.methods().next().unwrap();
+        assert_eq!(method.name().unwrap().text(), "f");
+        assert_eq!(method.return_type().unwrap().syntax().to_string(), "int");
+        let params: Vec<String> = method
+            .param_list()
+            .unwrap()
+            .params()
+            .map(|param| param.name().unwrap().text().to_owned())
+            .collect();
+        assert_eq!(params, ["a", "b"]);
+
+        let stmts: Vec<Stmt> = method.body().unwrap().statements().collect();
+        let Stmt::VarDecl(var) = &stmts[0] else {
+            panic!("expected declaration, got {:?}", stmts[0]);
+        };
+        let Some(Expr::BinExpr(mul)) = var.value() else {
+            panic!("expected binary expression, got {:?}", var.value());
+        };
+        assert_eq!(mul.op().unwrap().text(), "*");
+        assert_eq!(mul.rhs().unwrap().syntax().to_string(), "(b + 1)");
+
+        let Stmt::ReturnStmt(ret) = &stmts[1] else {
+            panic!("expected return, got {:?}", stmts[1]);
+        };
+        let Some(Expr::PropertyExpr(property)) = ret.expr() else {
+            panic!("expected property, got {:?}", ret.expr());
+        };
+        assert_eq!(property.name().unwrap().text(), "y");
+    }
+
+    #[test]
+    fn nodes_starting_together_are_nested() {
+        let input = "class A { void f() { int x = a - b - c.d.e } }";
+        let file = GroovyParser::parse(input).source_file();
+        let method = file.classes().next().unwrap().methods().next().unwrap();
+        let Some(Stmt::VarDecl(var)) = method.body().unwrap().statements().next() else {
+            panic!("expected declaration");
+        };
+        let Some(Expr::BinExpr(outer)) = var.value() else {
+            panic!("expected binary expression, got {:?}", var.value());
+        };
+        assert_eq!(outer.syntax().to_string(), "a - b - c.d.e");
+        let Some(Expr::BinExpr(inner)) = outer.lhs() else {
+            panic!("expected binary expression, got {:?}", outer.lhs());
+        };
+        assert_eq!(inner.syntax().to_string(), "a - b");
+        let Some(Expr::PropertyExpr(property)) = outer.rhs() else {
+            panic!("expected property, got {:?}", outer.rhs());
+        };
+        assert_eq!(property.name().unwrap().text(), "e");
+        assert_eq!(property.object().unwrap().syntax().to_string(), "c.d");
+    }
+
+    #[test]
+    fn edits_are_tree_rewrites() {
+        let input = "class A {\n  int f(int a) { return a }\n}\n";
+        let file = GroovyParser::parse(input).source_file();
+        let name = file.classes().next().unwrap().name().unwrap();
+        let renamed = replace_token(&name, "Renamed");
+        assert_eq!(
+            renamed.to_string(),
+            input.replace("class A", "class Renamed")
+        );
+        assert_eq!(file.syntax().to_string(), input);
+    }
+}
@@ -0,0 +1,756 @@
+//! Typed views of the nodes of the syntax tree. Each is a thin wrapper around
+//! a `SyntaxNode` of the matching kind, and every accessor returns `None`
+//! when the part it looks for is missing from malformed code
+
+use super::{SyntaxKind, SyntaxNode, SyntaxToken};
+
+pub trait CstNode: Sized {
+    fn cast(node: SyntaxNode) -> Option<Self>;
+
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+macro_rules! cst_nodes {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            pub struct $name(SyntaxNode);
+
+            impl CstNode for $name {
+                fn cast(node: SyntaxNode) -> Option<Self> {
+                    (node.kind() == SyntaxKind::$name).then(|| Self(node))
+                }
+
+                fn syntax(&self) -> &SyntaxNode {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+cst_nodes!(
+    SourceFile,
+    PackageDecl,
+    ImportDecl,
+    QualifiedName,
+    ClassDecl,
+    ExtendsClause,
+    ImplementsClause,
+    PermitsClause,
+    EnumConstant,
+    FieldDecl,
+    Declarator,
+    Initializer,
+    MethodDecl,
+    ParamList,
+    Param,
+    ThrowsClause,
+    Annotation,
+    AnnotationElement,
+    TypeRef,
+    TypeArgList,
+    TypeParamList,
+    TypeParam,
+    ArgList,
+    Block,
+    VarDecl,
+    ReturnStmt,
+    AssertStmt,
+    ExprStmt,
+    IfStmt,
+    WhileStmt,
+    DoWhileStmt,
+    ForStmt,
+    BreakStmt,
+    ContinueStmt,
+    LabeledStmt,
+    ForInit,
+    ForUpdate,
+    BinExpr,
+    AssignExpr,
+    PrefixExpr,
+    PostfixExpr,
+    LiteralExpr,
+    NameRef,
+    ParenExpr,
+    PropertyExpr,
+    CallExpr,
+    GStringExpr,
+);
+
+/// Either a statement node, or a block nested directly inside another
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Stmt {
+    VarDecl(VarDecl),
+    ReturnStmt(ReturnStmt),
+    AssertStmt(AssertStmt),
+    ExprStmt(ExprStmt),
+    IfStmt(IfStmt),
+    WhileStmt(WhileStmt),
+    DoWhileStmt(DoWhileStmt),
+    ForStmt(ForStmt),
+    BreakStmt(BreakStmt),
+    ContinueStmt(ContinueStmt),
+    LabeledStmt(LabeledStmt),
+    Block(Block),
+}
+
+impl CstNode for Stmt {
+    fn cast(node: SyntaxNode) -> Option<Self> {
+        Some(match node.kind() {
+            SyntaxKind::VarDecl => Stmt::VarDecl(VarDecl(node)),
+            SyntaxKind::ReturnStmt => Stmt::ReturnStmt(ReturnStmt(node)),
+            SyntaxKind::AssertStmt => Stmt::AssertStmt(AssertStmt(node)),
+            SyntaxKind::ExprStmt => Stmt::ExprStmt(ExprStmt(node)),
+            SyntaxKind::IfStmt => Stmt::IfStmt(IfStmt(node)),
+            SyntaxKind::WhileStmt => Stmt::WhileStmt(WhileStmt(node)),
+            SyntaxKind::DoWhileStmt => Stmt::DoWhileStmt(DoWhileStmt(node)),
+            SyntaxKind::ForStmt => Stmt::ForStmt(ForStmt(node)),
+            SyntaxKind::BreakStmt => Stmt::BreakStmt(BreakStmt(node)),
+            SyntaxKind::ContinueStmt => Stmt::ContinueStmt(ContinueStmt(node)),
+            SyntaxKind::LabeledStmt => Stmt::LabeledStmt(LabeledStmt(node)),
+            SyntaxKind::Block => Stmt::Block(Block(node)),
+            _ => return None,
+        })
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Stmt::VarDecl(node) => node.syntax(),
+            Stmt::ReturnStmt(node) => node.syntax(),
+            Stmt::AssertStmt(node) => node.syntax(),
+            Stmt::ExprStmt(node) => node.syntax(),
+            Stmt::IfStmt(node) => node.syntax(),
+            Stmt::WhileStmt(node) => node.syntax(),
+            Stmt::DoWhileStmt(node) => node.syntax(),
+            Stmt::ForStmt(node) => node.syntax(),
+            Stmt::BreakStmt(node) => node.syntax(),
+            Stmt::ContinueStmt(node) => node.syntax(),
+            Stmt::LabeledStmt(node) => node.syntax(),
+            Stmt::Block(node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr {
+    BinExpr(BinExpr),
+    AssignExpr(AssignExpr),
+    PrefixExpr(PrefixExpr),
+    PostfixExpr(PostfixExpr),
+    LiteralExpr(LiteralExpr),
+    NameRef(NameRef),
+    ParenExpr(ParenExpr),
+    PropertyExpr(PropertyExpr),
+    CallExpr(CallExpr),
+    GStringExpr(GStringExpr),
+}
+
+impl CstNode for Expr {
+    fn cast(node: SyntaxNode) -> Option<Self> {
+        Some(match node.kind() {
+            SyntaxKind::BinExpr => Expr::BinExpr(BinExpr(node)),
+            SyntaxKind::AssignExpr => Expr::AssignExpr(AssignExpr(node)),
+            SyntaxKind::PrefixExpr => Expr::PrefixExpr(PrefixExpr(node)),
+            SyntaxKind::PostfixExpr => Expr::PostfixExpr(PostfixExpr(node)),
+            SyntaxKind::LiteralExpr => Expr::LiteralExpr(LiteralExpr(node)),
+            SyntaxKind::NameRef => Expr::NameRef(NameRef(node)),
+            SyntaxKind::ParenExpr => Expr::ParenExpr(ParenExpr(node)),
+            SyntaxKind::PropertyExpr => Expr::PropertyExpr(PropertyExpr(node)),
+            SyntaxKind::CallExpr => Expr::CallExpr(CallExpr(node)),
+            SyntaxKind::GStringExpr => Expr::GStringExpr(GStringExpr(node)),
+            _ => return None,
+        })
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Expr::BinExpr(node) => node.syntax(),
+            Expr::AssignExpr(node) => node.syntax(),
+            Expr::PrefixExpr(node) => node.syntax(),
+            Expr::PostfixExpr(node) => node.syntax(),
+            Expr::LiteralExpr(node) => node.syntax(),
+            Expr::NameRef(node) => node.syntax(),
+            Expr::ParenExpr(node) => node.syntax(),
+            Expr::PropertyExpr(node) => node.syntax(),
+            Expr::CallExpr(node) => node.syntax(),
+            Expr::GStringExpr(node) => node.syntax(),
+        }
+    }
+}
+
+fn child<N: CstNode>(parent: &SyntaxNode) -> Option<N> {
+    parent.children().find_map(N::cast)
+}
+
+fn children<N: CstNode>(parent: &SyntaxNode) -> impl Iterator<Item = N> {
+    parent.children().filter_map(N::cast)
+}
+
+/// The first token of `kind` which is a direct child of `parent`
+fn token(parent: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    parent
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| token.kind() == kind)
+}
+
+impl SourceFile {
+    pub fn package(&self) -> Option<PackageDecl> {
+        child(&self.0)
+    }
+
+    pub fn imports(&self) -> impl Iterator<Item = ImportDecl> {
+        children(&self.0)
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = ClassDecl> {
+        children(&self.0)
+    }
+
+    /// Methods declared outside of any class
+    pub fn methods(&self) -> impl Iterator<Item = MethodDecl> {
+        children(&self.0)
+    }
+
+    /// The statements of the script
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.0)
+    }
+}
+
+impl PackageDecl {
+    pub fn annotations(&self) -> impl Iterator<Item = Annotation> {
+        children(&self.0)
+    }
+
+    pub fn name(&self) -> Option<QualifiedName> {
+        child(&self.0)
+    }
+}
+
+impl ImportDecl {
+    pub fn annotations(&self) -> impl Iterator<Item = Annotation> {
+        children(&self.0)
+    }
+
+    pub fn name(&self) -> Option<QualifiedName> {
+        child(&self.0)
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.0.children_with_tokens().any(|element| {
+            element
+                .as_token()
+                .is_some_and(|token| token.text() == "static")
+        })
+    }
+
+    pub fn is_star(&self) -> bool {
+        token(&self.0, SyntaxKind::Operator).is_some_and(|token| token.text() == "*")
+    }
+
+    /// The name given with `as`
+    pub fn alias(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl QualifiedName {
+    pub fn segments(&self) -> impl Iterator<Item = SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| token.kind() == SyntaxKind::Ident)
+    }
+}
+
+impl ClassDecl {
+    pub fn annotations(&self) -> impl Iterator<Item = Annotation> {
+        children(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn type_param_list(&self) -> Option<TypeParamList> {
+        child(&self.0)
+    }
+
+    pub fn extends_clause(&self) -> Option<ExtendsClause> {
+        child(&self.0)
+    }
+
+    pub fn implements_clause(&self) -> Option<ImplementsClause> {
+        child(&self.0)
+    }
+
+    pub fn permits_clause(&self) -> Option<PermitsClause> {
+        child(&self.0)
+    }
+
+    /// The components of a record
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn enum_constants(&self) -> impl Iterator<Item = EnumConstant> {
+        children(&self.0)
+    }
+
+    /// Field and property declarations
+    pub fn fields(&self) -> impl Iterator<Item = FieldDecl> {
+        children(&self.0)
+    }
+
+    pub fn initializers(&self) -> impl Iterator<Item = Initializer> {
+        children(&self.0)
+    }
+
+    pub fn methods(&self) -> impl Iterator<Item = MethodDecl> {
+        children(&self.0)
+    }
+}
+
+impl TypeRef {
+    /// The last segment of the name, which is missing for primitive types
+    /// and wildcards
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| token.kind() == SyntaxKind::Ident)
+            .last()
+    }
+
+    /// The number of `[]` following the type
+    pub fn array_dimensions(&self) -> usize {
+        self.0
+            .children_with_tokens()
+            .filter(|element| element.kind() == SyntaxKind::SquareBraceOpen)
+            .count()
+    }
+
+    pub fn type_arg_list(&self) -> Option<TypeArgList> {
+        child(&self.0)
+    }
+}
+
+impl TypeArgList {
+    pub fn types(&self) -> impl Iterator<Item = TypeRef> {
+        children(&self.0)
+    }
+}
+
+impl TypeParamList {
+    pub fn params(&self) -> impl Iterator<Item = TypeParam> {
+        children(&self.0)
+    }
+}
+
+impl TypeParam {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn bounds(&self) -> impl Iterator<Item = TypeRef> {
+        children(&self.0)
+    }
+}
+
+impl ExtendsClause {
+    pub fn types(&self) -> impl Iterator<Item = TypeRef> {
+        children(&self.0)
+    }
+}
+
+impl ImplementsClause {
+    pub fn types(&self) -> impl Iterator<Item = TypeRef> {
+        children(&self.0)
+    }
+}
+
+impl PermitsClause {
+    pub fn types(&self) -> impl Iterator<Item = TypeRef> {
+        children(&self.0)
+    }
+}
+
+impl EnumConstant {
+    pub fn annotations(&self) -> impl Iterator<Item = Annotation> {
+        children(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+
+    /// The methods of the constant's own body
+    pub fn methods(&self) -> impl Iterator<Item = MethodDecl> {
+        children(&self.0)
+    }
+}
+
+impl FieldDecl {
+    pub fn annotations(&self) -> impl Iterator<Item = Annotation> {
+        children(&self.0)
+    }
+
+    /// The declared type, which is missing for untyped fields
+    pub fn ty(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    /// Each of the fields declared, `a = 1` and `b` in `int a = 1, b`
+    pub fn declarators(&self) -> impl Iterator<Item = Declarator> {
+        children(&self.0)
+    }
+}
+
+impl Declarator {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl Initializer {
+    pub fn is_static(&self) -> bool {
+        token(&self.0, SyntaxKind::Keyword).is_some()
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+impl ThrowsClause {
+    pub fn types(&self) -> impl Iterator<Item = TypeRef> {
+        children(&self.0)
+    }
+}
+
+impl Annotation {
+    pub fn name(&self) -> Option<QualifiedName> {
+        child(&self.0)
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = AnnotationElement> {
+        children(&self.0)
+    }
+}
+
+impl AnnotationElement {
+    /// The name of the element, which is missing for the implicit `value`
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl MethodDecl {
+    pub fn annotations(&self) -> impl Iterator<Item = Annotation> {
+        children(&self.0)
+    }
+
+    pub fn return_type(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn throws_clause(&self) -> Option<ThrowsClause> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+impl ArgList {
+    pub fn args(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}
+
+impl ParamList {
+    pub fn params(&self) -> impl Iterator<Item = Param> {
+        children(&self.0)
+    }
+}
+
+impl Param {
+    pub fn annotations(&self) -> impl Iterator<Item = Annotation> {
+        children(&self.0)
+    }
+
+    /// The declared type, which is missing for untyped parameters
+    pub fn ty(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    pub fn default_value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl Block {
+    pub fn statements(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.0)
+    }
+}
+
+impl VarDecl {
+    pub fn annotations(&self) -> impl Iterator<Item = Annotation> {
+        children(&self.0)
+    }
+
+    /// The declared type, which is missing for `def` and `var`
+    pub fn ty(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl ReturnStmt {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl AssertStmt {
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn message(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl ExprStmt {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl IfStmt {
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn then_branch(&self) -> Option<Stmt> {
+        child(&self.0)
+    }
+
+    /// The statement following `else`, which is another `IfStmt` for
+    /// `else if`
+    pub fn else_branch(&self) -> Option<Stmt> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl WhileStmt {
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Stmt> {
+        child(&self.0)
+    }
+}
+
+impl DoWhileStmt {
+    pub fn body(&self) -> Option<Stmt> {
+        child(&self.0)
+    }
+
+    pub fn condition(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl ForStmt {
+    /// The variable of a `for (x in xs)` loop, which is missing for classic
+    /// loops
+    pub fn variable(&self) -> Option<Param> {
+        child(&self.0)
+    }
+
+    /// The collection iterated over by a `for (x in xs)` loop
+    pub fn collection(&self) -> Option<Expr> {
+        self.variable().and_then(|_| child(&self.0))
+    }
+
+    pub fn init(&self) -> Option<ForInit> {
+        child(&self.0)
+    }
+
+    /// The condition of a classic loop
+    pub fn condition(&self) -> Option<Expr> {
+        match self.variable() {
+            Some(..) => None,
+            None => child(&self.0),
+        }
+    }
+
+    pub fn update(&self) -> Option<ForUpdate> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Stmt> {
+        child(&self.0)
+    }
+}
+
+impl BreakStmt {
+    pub fn label(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl ContinueStmt {
+    pub fn label(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl LabeledStmt {
+    pub fn label(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn stmt(&self) -> Option<Stmt> {
+        child(&self.0)
+    }
+}
+
+impl ForInit {
+    /// The type of the declared variables, which is missing for `def`, `var`
+    /// and when the init is a list of expressions
+    pub fn ty(&self) -> Option<TypeRef> {
+        child(&self.0)
+    }
+
+    pub fn declarators(&self) -> impl Iterator<Item = Declarator> {
+        children(&self.0)
+    }
+
+    pub fn exprs(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}
+
+impl ForUpdate {
+    pub fn exprs(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}
+
+impl BinExpr {
+    pub fn lhs(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+
+    pub fn op(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Operator)
+    }
+}
+
+impl AssignExpr {
+    pub fn target(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+}
+
+impl PrefixExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl PostfixExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl LiteralExpr {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        self.0.first_token()
+    }
+}
+
+impl NameRef {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl ParenExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl PropertyExpr {
+    pub fn object(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl CallExpr {
+    /// The name or property being called
+    pub fn callee(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+}
+
+impl GStringExpr {
+    /// The interpolated expressions, in order
+    pub fn interpolations(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}